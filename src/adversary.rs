@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+
+use ark_bls12_381::{G1Affine, G1Projective};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::Field;
+use ed25519_dalek::*;
+
+use crate::error::{Error, Result};
+use crate::vss::{signed_bytes, Endorsement, Subshare};
+
+///Byzantine adversary that takes over the corrupted parties of a protocol run.
+///Parties are numbered as in the protocol: in the VSS the dealer is 0, receiver i is i and reconstructor j is n+j, in the extractor party i is i.
+///The protocols only consult the hooks for corrupted parties, right before their messages go out;
+///the hooks get the party's signing key so that tampered messages can carry its valid signature,
+///and receivers also get the dealer's key, which names the dealing their endorsements are bound to.
+pub trait Adversary {
+    fn is_corrupted(&self, party: u64) -> bool;
+
+    ///Rows the VSS dealer sends, indexed by receiver; `points` are the receivers' evaluation points and `degree` the dealing's
+    fn tamper_rows<F: Field>(&mut self, _dealer: u64, _rows: &mut BTreeMap<u64, BTreeMap<u64, Subshare<F>>>, _points: &[F], _degree: u64, _keypair: &Keypair) {}
+
+    ///Doubly signed subshares a VSS receiver sends to the later receivers, indexed by recipient
+    fn tamper_doubly_signed<F: Field>(&mut self, _sender: u64, _subshares: &mut BTreeMap<u64, Subshare<F>>, _keypair: &Keypair, _dealer_pk: &PublicKey) {}
+
+    ///Triply signed subshares a VSS receiver forwards to the reconstructors, indexed by the receiver they came from
+    fn tamper_forwarded<F: Field>(&mut self, _sender: u64, _subshares: &mut BTreeMap<u64, Subshare<F>>, _keypair: &Keypair, _dealer_pk: &PublicKey) {}
+
+    ///Triply signed subshares a VSS reconstructor publishes, indexed by the receiver that forwarded them
+    fn tamper_publication<F: Field>(&mut self, _reconstructor: u64, _publication: &mut BTreeMap<u64, BTreeMap<u64, Subshare<F>>>) {}
+
+    ///Values a resharing holder deals to the next committee, one per packed slot
+    fn tamper_reshared<F: Field>(&mut self, _holder: u64, _values: &mut Vec<F>) {}
+
+    ///Commitment a DKG dealer publishes next to its rows
+    fn tamper_commitment(&mut self, _dealer: u64, _commitment: &mut Vec<G1Affine>) {}
+
+    ///Subset values an extractor leader sends, indexed by verifier and then by subset
+    fn tamper_leader_values(&mut self, _leader: u64, _values: &mut BTreeMap<usize, BTreeMap<usize, i32>>) {}
+
+    ///Leader values an extractor verifier forwards to the later verifiers
+    fn tamper_forwarded_values(&mut self, _verifier: u64, _values: &mut BTreeMap<usize, BTreeMap<usize, i32>>) {}
+
+    ///Verified subset values an extractor verifier sends to the publishers
+    fn tamper_verified_values(&mut self, _verifier: u64, _values: &mut BTreeMap<usize, BTreeMap<usize, i32>>) {}
+
+    ///Subset results an extractor publisher publishes
+    fn tamper_published(&mut self, _publisher: u64, _values: &mut BTreeMap<usize, i32>) {}
+
+    ///Payload a reliable-broadcast sender sends to `recipient`
+    fn tamper_broadcast(&mut self, _sender: u64, _recipient: u64, _payload: &mut Vec<u8>) {}
+}
+
+///Adversary that corrupts nobody
+pub struct Honest;
+
+impl Adversary for Honest {
+    fn is_corrupted(&self, _party: u64) -> bool {
+        false
+    }
+}
+
+///Misbehavior of a corrupted party
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Behavior {
+    ///VSS dealer: the row of the lowest-indexed receiver no longer lies on a polynomial of the dealing's degree
+    BadDegreeRow,
+    ///VSS receiver: every subshare it sends or forwards carries a wrong value; VSS reconstructor: so does every subshare it publishes
+    WrongForward,
+    ///Extractor leader: verifiers with even index get the flipped value of each led subset;
+    ///broadcast sender: parties with even index get a payload with every bit flipped
+    Equivocate,
+    ///Extractor publisher: every published result is flipped
+    LyingPublisher,
+    ///VSS receiver: its subshares go out without its signature; extractor leader: it sends values that are not bits
+    Garbage,
+    ///DKG dealer: its commitment is to a different secret than the one it deals
+    WrongCommitment,
+    ///Resharing holder: deals its share plus one in every slot
+    WrongShare,
+    ///Sends nothing at all
+    Silent,
+}
+
+///Adversary corrupting up to t chosen parties, each with a fixed behavior
+pub struct Corruption {
+    pub t: u64,
+    pub behaviors: BTreeMap<u64, Behavior>,
+}
+
+//A tampered value at (i, j) is always endorsed with a plain signature, whatever the honest parties use
+fn sign_value<F: Field>(keypair: &Keypair, value: &F, dealer_pk: &PublicKey, i: u64, j: u64) -> Endorsement {
+    Endorsement::Signature(keypair.sign(&signed_bytes(value, dealer_pk, i, j).unwrap()))
+}
+
+fn flip_all(values: &mut BTreeMap<usize, i32>) {
+    for value in values.values_mut() {
+        *value ^= 1;
+    }
+}
+
+impl Corruption {
+    pub fn new(t: u64) -> Corruption {
+        Corruption { t: t, behaviors: BTreeMap::new() }
+    }
+
+    pub fn corrupt(mut self, party: u64, behavior: Behavior) -> Result<Corruption> {
+        self.behaviors.insert(party, behavior);
+        if self.behaviors.len() as u64 > self.t {
+            return Err(Error::InvalidParams(format!("cannot corrupt more than {} parties", self.t)));
+        }
+        Ok(self)
+    }
+
+    fn behaves(&self, party: u64, behavior: Behavior) -> bool {
+        self.behaviors.get(&party) == Some(&behavior)
+    }
+}
+
+impl Adversary for Corruption {
+    fn is_corrupted(&self, party: u64) -> bool {
+        self.behaviors.contains_key(&party)
+    }
+
+    fn tamper_rows<F: Field>(&mut self, dealer: u64, rows: &mut BTreeMap<u64, BTreeMap<u64, Subshare<F>>>, points: &[F], degree: u64, keypair: &Keypair) {
+        if self.behaves(dealer, Behavior::Silent) {
+            rows.clear();
+        }
+        if self.behaves(dealer, Behavior::BadDegreeRow) {
+            //Adding y^(degree+1) at every receiver's point y moves the row to a polynomial of degree exactly degree+1
+            if let Some((i, row)) = rows.iter_mut().next() {
+                for (j, subshare) in row.iter_mut() {
+                    subshare.value += points[(*j - 1) as usize].pow([degree + 1]);
+                    subshare.dealer_signature = Some(sign_value(keypair, &subshare.value, &keypair.public, *i, *j));
+                }
+            }
+        }
+    }
+
+    fn tamper_doubly_signed<F: Field>(&mut self, sender: u64, subshares: &mut BTreeMap<u64, Subshare<F>>, keypair: &Keypair, dealer_pk: &PublicKey) {
+        if self.behaves(sender, Behavior::Silent) {
+            subshares.clear();
+        }
+        if self.behaves(sender, Behavior::WrongForward) {
+            //The sender can sign the wrong value itself but not on behalf of the dealer
+            for (k, subshare) in subshares.iter_mut() {
+                subshare.value += F::ONE;
+                subshare.p_i_signature = Some(sign_value(keypair, &subshare.value, dealer_pk, sender, *k));
+            }
+        }
+        if self.behaves(sender, Behavior::Garbage) {
+            for subshare in subshares.values_mut() {
+                subshare.p_i_signature = None;
+            }
+        }
+    }
+
+    fn tamper_forwarded<F: Field>(&mut self, sender: u64, subshares: &mut BTreeMap<u64, Subshare<F>>, keypair: &Keypair, dealer_pk: &PublicKey) {
+        if self.behaves(sender, Behavior::Silent) {
+            subshares.clear();
+        }
+        if self.behaves(sender, Behavior::WrongForward) {
+            for (j, subshare) in subshares.iter_mut() {
+                subshare.value += F::ONE;
+                subshare.p_j_signature = Some(sign_value(keypair, &subshare.value, dealer_pk, *j, sender));
+            }
+        }
+        if self.behaves(sender, Behavior::Garbage) {
+            for subshare in subshares.values_mut() {
+                subshare.p_j_signature = None;
+            }
+        }
+    }
+
+    fn tamper_publication<F: Field>(&mut self, reconstructor: u64, publication: &mut BTreeMap<u64, BTreeMap<u64, Subshare<F>>>) {
+        if self.behaves(reconstructor, Behavior::Silent) {
+            publication.clear();
+        }
+        if self.behaves(reconstructor, Behavior::WrongForward) {
+            //The endorsements stay those of the honest values
+            for subshare in publication.values_mut().flat_map(|row| row.values_mut()) {
+                subshare.value += F::ONE;
+            }
+        }
+    }
+
+    fn tamper_reshared<F: Field>(&mut self, holder: u64, values: &mut Vec<F>) {
+        if self.behaves(holder, Behavior::WrongShare) {
+            for value in values.iter_mut() {
+                *value += F::ONE;
+            }
+        }
+    }
+
+    fn tamper_commitment(&mut self, dealer: u64, commitment: &mut Vec<G1Affine>) {
+        if self.behaves(dealer, Behavior::Silent) {
+            commitment.clear();
+        }
+        if self.behaves(dealer, Behavior::WrongCommitment) {
+            if let Some(constant) = commitment.first_mut() {
+                *constant = (*constant + G1Projective::generator()).into_affine();
+            }
+        }
+    }
+
+    fn tamper_leader_values(&mut self, leader: u64, values: &mut BTreeMap<usize, BTreeMap<usize, i32>>) {
+        if self.behaves(leader, Behavior::Silent) {
+            values.clear();
+        }
+        if self.behaves(leader, Behavior::Equivocate) {
+            for (_, to_verifier) in values.iter_mut().filter(|(verifier, _)| *verifier % 2 == 0) {
+                flip_all(to_verifier);
+            }
+        }
+        if self.behaves(leader, Behavior::Garbage) {
+            for value in values.values_mut().flat_map(|to_verifier| to_verifier.values_mut()) {
+                *value = 2;
+            }
+        }
+    }
+
+    fn tamper_forwarded_values(&mut self, verifier: u64, values: &mut BTreeMap<usize, BTreeMap<usize, i32>>) {
+        if self.behaves(verifier, Behavior::Silent) {
+            values.clear();
+        }
+    }
+
+    fn tamper_verified_values(&mut self, verifier: u64, values: &mut BTreeMap<usize, BTreeMap<usize, i32>>) {
+        if self.behaves(verifier, Behavior::Silent) {
+            values.clear();
+        }
+    }
+
+    fn tamper_published(&mut self, publisher: u64, values: &mut BTreeMap<usize, i32>) {
+        if self.behaves(publisher, Behavior::Silent) {
+            values.clear();
+        }
+        if self.behaves(publisher, Behavior::LyingPublisher) {
+            flip_all(values);
+        }
+    }
+
+    fn tamper_broadcast(&mut self, sender: u64, recipient: u64, payload: &mut Vec<u8>) {
+        if self.behaves(sender, Behavior::Equivocate) && recipient.is_multiple_of(2) {
+            for byte in payload.iter_mut() {
+                *byte = !*byte;
+            }
+        }
+    }
+}
+
+///One of several parallel dealings among the same committee as seen by the adversary of the whole run:
+///the dealing's dealer 0 is party `dealer`, every other party keeps its number
+pub struct DealingView<'a, A: Adversary> {
+    pub adversary: &'a mut A,
+    pub dealer: u64,
+}
+
+impl<A: Adversary> DealingView<'_, A> {
+    fn party(&self, party: u64) -> u64 {
+        if party == 0 { self.dealer } else { party }
+    }
+}
+
+impl<A: Adversary> Adversary for DealingView<'_, A> {
+    fn is_corrupted(&self, party: u64) -> bool {
+        self.adversary.is_corrupted(self.party(party))
+    }
+
+    fn tamper_rows<F: Field>(&mut self, dealer: u64, rows: &mut BTreeMap<u64, BTreeMap<u64, Subshare<F>>>, points: &[F], degree: u64, keypair: &Keypair) {
+        let dealer = self.party(dealer);
+        self.adversary.tamper_rows(dealer, rows, points, degree, keypair)
+    }
+
+    fn tamper_doubly_signed<F: Field>(&mut self, sender: u64, subshares: &mut BTreeMap<u64, Subshare<F>>, keypair: &Keypair, dealer_pk: &PublicKey) {
+        self.adversary.tamper_doubly_signed(sender, subshares, keypair, dealer_pk)
+    }
+
+    fn tamper_forwarded<F: Field>(&mut self, sender: u64, subshares: &mut BTreeMap<u64, Subshare<F>>, keypair: &Keypair, dealer_pk: &PublicKey) {
+        self.adversary.tamper_forwarded(sender, subshares, keypair, dealer_pk)
+    }
+
+    fn tamper_publication<F: Field>(&mut self, reconstructor: u64, publication: &mut BTreeMap<u64, BTreeMap<u64, Subshare<F>>>) {
+        self.adversary.tamper_publication(reconstructor, publication)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fq;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::polynomials::{Poly, SymBVPoly};
+    use crate::role_assignment::generate_keypair;
+
+    use super::*;
+
+    #[test]
+    fn a_bad_degree_row_is_off_every_polynomial_of_the_dealing_degree() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let (t, degree) = (1, 2);
+        //Evaluation points unrelated to the receiver indices, as the role keys give them
+        let points: Vec<Fq> = [17u64, 5, 91, 42, 8, 33].iter().map(|x| Fq::from(*x)).collect();
+        let poly: SymBVPoly<Fq> = SymBVPoly::rand(degree, &mut rng);
+        let mut rows: BTreeMap<u64, BTreeMap<u64, Subshare<Fq>>> = (1..=points.len() as u64)
+            .map(|i| (i, (1..=points.len() as u64)
+                .map(|j| (j, Subshare { value: poly.eval(points[(i - 1) as usize], points[(j - 1) as usize]), dealer_signature: None, p_i_signature: None, p_j_signature: None }))
+                .collect()))
+            .collect();
+        let keypair = generate_keypair(&mut rng);
+        Corruption::new(t).corrupt(0, Behavior::BadDegreeRow).unwrap().tamper_rows(0, &mut rows, &points, degree, &keypair);
+
+        let row_degree = |i: u64| {
+            let values: Vec<Fq> = rows[&i].values().map(|subshare| subshare.value).collect();
+            Poly::evals_to_coeffs(&points, &values).unwrap().degree
+        };
+        assert_eq!(row_degree(1), degree + 1);
+        assert!(rows[&1].values().all(|subshare| subshare.dealer_signature.is_some()));
+        assert!((2..=points.len() as u64).all(|i| row_degree(i) <= degree));
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use ark_ff::Field;
+use ed25519_dalek::PublicKey;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::board::to_hex;
+use crate::error::Result;
+use crate::metrics::CostReport;
+use crate::vss::{add_values, display_values, encode_publication, points_of, Client, Subshare};
+
+//The auditor is the client of the VSS run by an outsider: it sees only the role keys, what the reconstructors
+//published and the value it is told came out. It trusts none of the parties, only the signatures, and needs
+//one reconstructor to have published honestly; for the beacon it takes the qualified dealings as given.
+
+///What the auditor concluded about one dealing
+#[derive(Clone, Debug, Serialize)]
+pub struct DealingCertificate {
+    ///SHA-256 of the dealing's role keys, in pki order
+    pub keys: String,
+    ///SHA-256 of each publication, in the order they were audited
+    pub publications: Vec<String>,
+    ///Rows the secrets were interpolated from
+    pub rows: Vec<u64>,
+    ///One secret per packed slot
+    pub secrets: Option<Vec<String>>,
+}
+
+///Compact record of an audit to keep next to the output; the digests tie it to the keys and publications it is about
+#[derive(Clone, Debug, Serialize)]
+pub struct Certificate {
+    pub t: u64,
+    pub n: u64,
+    pub packing: u64,
+    ///One value per packed slot
+    pub output: Vec<String>,
+    pub passed: bool,
+    pub dealings: Vec<DealingCertificate>,
+}
+
+///Pass/fail verdict on a claimed output with the reasons behind it
+pub struct Verdict {
+    pub passed: bool,
+    ///Rejected rows and publications, and why the output was not accepted if it was not
+    pub reasons: Vec<String>,
+    pub certificate: Certificate,
+}
+
+///Checks outputs of the VSS and the beacon from public data only
+pub struct Auditor {
+    pub t: u64,
+    pub n: u64,
+    //Number of secrets packed into each dealing
+    pub packing: u64,
+}
+
+fn digest(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+impl Auditor {
+    pub fn new(t: u64, n: u64) -> Auditor {
+        Auditor { t: t, n: n, packing: 1 }
+    }
+
+    ///The auditor of dealings with `packing` secrets each
+    pub fn with_packing(mut self, packing: u64) -> Auditor {
+        self.packing = packing;
+        self
+    }
+
+    ///Audits one dealing: `claimed` must be what every publication that opens at all opens to, and at least one must open
+    pub fn audit<F: Field>(&self, pki: &[PublicKey], publications: &[BTreeMap<u64, BTreeMap<u64, Subshare<F>>>], claimed: &[F]) -> Result<Verdict> {
+        let mut reasons = Vec::new();
+        let (secrets, dealing) = self.open(pki, publications, &mut reasons)?;
+        let passed = secrets.as_deref() == Some(claimed);
+        if let Some(secrets) = secrets.filter(|secrets| secrets != claimed) {
+            reasons.push(format!("the publications open to {}, not to {}", display_values(&secrets), display_values(claimed)));
+        }
+        Ok(Verdict { passed: passed, reasons: reasons, certificate: self.certificate(claimed, passed, vec![dealing]) })
+    }
+
+    ///Audits a beacon output: the sum of the secrets the qualified dealings open to, given as their role keys and publications
+    pub fn audit_beacon<F: Field>(&self, dealings: &[(Vec<PublicKey>, Vec<BTreeMap<u64, BTreeMap<u64, Subshare<F>>>>)], claimed: &[F]) -> Result<Verdict> {
+        let mut reasons = Vec::new();
+        let mut output = Some(vec![F::ZERO; self.packing as usize]);
+        let mut certificates = Vec::new();
+        for (index, (pki, publications)) in dealings.iter().enumerate() {
+            let mut dealing_reasons = Vec::new();
+            let (secrets, certificate) = self.open(pki, publications, &mut dealing_reasons)?;
+            reasons.extend(dealing_reasons.into_iter().map(|reason| format!("dealing {}: {}", index + 1, reason)));
+            output = output.zip(secrets).map(|(mut output, secrets)| {
+                add_values(&mut output, &secrets);
+                output
+            });
+            certificates.push(certificate);
+        }
+        let passed = output.as_deref() == Some(claimed);
+        if let Some(output) = output.filter(|output| output != claimed) {
+            reasons.push(format!("the dealings add up to {}, not to {}", display_values(&output), display_values(claimed)));
+        }
+        Ok(Verdict { passed: passed, reasons: reasons, certificate: self.certificate(claimed, passed, certificates) })
+    }
+
+    //Runs the client's checks on every publication; returns the secrets they agree on, if they do and one opens
+    fn open<F: Field>(&self, pki: &[PublicKey], publications: &[BTreeMap<u64, BTreeMap<u64, Subshare<F>>>], reasons: &mut Vec<String>)
+                                                -> Result<(Option<Vec<F>>, DealingCertificate)> {
+        let keys: Vec<u8> = pki.iter().flat_map(|pk| pk.to_bytes()).collect();
+        let mut certificate = DealingCertificate {
+            keys: digest(&keys),
+            publications: publications.iter().map(|publication| Ok(digest(&encode_publication(publication)?))).collect::<Result<Vec<String>>>()?,
+            rows: Vec::new(),
+            secrets: None,
+        };
+        if pki.len() as u64 != self.n + 1 {
+            reasons.push(format!("{} role keys for a dealer and {} receivers", pki.len(), self.n));
+            return Ok((None, certificate));
+        }
+        let points: Vec<F> = match points_of(&pki[1..], self.packing) {
+            Ok((points, _)) => points,
+            Err(error) => {
+                reasons.push(error.to_string());
+                return Ok((None, certificate));
+            }
+        };
+
+        let client: Client<F> = Client::new(self.t, self.n).with_packing(self.packing);
+        let mut openings: Vec<Vec<F>> = Vec::new();
+        for (index, publication) in publications.iter().enumerate() {
+            //Every row is checked, so that each rejected one comes with its reason
+            let mut verified_rows = Vec::new();
+            for i in 1..=self.n {
+                match client.verify_row(i, publication, pki, &points) {
+                    Ok(_) => verified_rows.push(i),
+                    Err(error) => reasons.push(format!("publication {}, row {}: {}", index + 1, i, error)),
+                }
+            }
+            match client.compute_secret(std::slice::from_ref(publication), pki, &mut CostReport::default()) {
+                Ok(secrets) => {
+                    if openings.is_empty() {
+                        //The client interpolates the first t+packing rows that verify
+                        certificate.rows = verified_rows.into_iter().take((self.t + self.packing) as usize).collect();
+                    }
+                    openings.push(secrets);
+                },
+                Err(error) => reasons.push(format!("publication {} does not open: {}", index + 1, error)),
+            }
+        }
+
+        match openings.first() {
+            Some(secrets) if openings.iter().all(|other| other == secrets) => {
+                certificate.secrets = Some(secrets.iter().map(|secret| secret.to_string()).collect());
+                Ok((Some(secrets.clone()), certificate))
+            },
+            Some(_) => {
+                reasons.push("publications open to different secrets".to_string());
+                Ok((None, certificate))
+            },
+            None => {
+                reasons.push("no publication opens".to_string());
+                Ok((None, certificate))
+            }
+        }
+    }
+
+    fn certificate<F: Field>(&self, output: &[F], passed: bool, dealings: Vec<DealingCertificate>) -> Certificate {
+        let output = output.iter().map(|value| value.to_string()).collect();
+        Certificate { t: self.t, n: self.n, packing: self.packing, output: output, passed: passed, dealings: dealings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fq;
+
+    use crate::adversary::Honest;
+    use crate::transcript::Transcript;
+    use crate::vss::{publications_of, PubParams, VSS};
+
+    use super::*;
+
+    //A recorded honest VSS dealing of 5 with t = 1 and 4 receivers
+    fn dealing() -> Transcript {
+        let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(5u64)], pp: PubParams::with_size_and_seed(1, 4, 9, 2).unwrap(), execution_leaks: false, seed: 2 };
+        vss.execute_recorded(&mut Honest).unwrap().1
+    }
+
+    #[test]
+    fn the_dealt_secret_is_certified_and_any_other_claim_rejected() {
+        let transcript = dealing();
+        let pki = transcript.keys_of(0).unwrap();
+        let publications = publications_of::<Fq>(&transcript, 0, 1, 4);
+        let auditor = Auditor::new(1, 4);
+
+        let verdict = auditor.audit(&pki, &publications, &[Fq::from(5u64)]).unwrap();
+        assert!(verdict.passed && verdict.reasons.is_empty());
+        assert_eq!(verdict.certificate.dealings[0].publications.len(), 2);
+        assert_eq!(verdict.certificate.dealings[0].secrets, Some(vec![Fq::from(5u64).to_string()]));
+
+        let verdict = auditor.audit(&pki, &publications, &[Fq::from(6u64)]).unwrap();
+        assert!(!verdict.passed && !verdict.certificate.passed);
+        assert_eq!(verdict.reasons.len(), 1);
+
+        let verdict = auditor.audit(&pki[1..], &publications, &[Fq::from(5u64)]).unwrap();
+        assert!(!verdict.passed);
+
+        //A beacon output is the sum over the dealings
+        let dealings = vec![(pki.clone(), publications.clone()), (pki, publications)];
+        assert!(auditor.audit_beacon(&dealings, &[Fq::from(10u64)]).unwrap().passed);
+        let verdict = auditor.audit_beacon(&dealings, &[Fq::from(5u64)]).unwrap();
+        assert!(!verdict.passed && verdict.certificate.dealings.len() == 2);
+    }
+
+    #[test]
+    fn an_altered_row_is_rejected_and_the_honest_rows_still_certify() {
+        let transcript = dealing();
+        let pki = transcript.keys_of(0).unwrap();
+        let mut publications = publications_of::<Fq>(&transcript, 0, 1, 4);
+        //Every subshare (4, j) changes, which leaves row 4 without a verified subshare and every other row one short
+        for subshare in publications[0].get_mut(&4).unwrap().values_mut() {
+            subshare.value += Fq::from(1u64);
+        }
+
+        let verdict = Auditor::new(1, 4).audit(&pki, &publications, &[Fq::from(5u64)]).unwrap();
+        assert!(verdict.passed);
+        assert!(verdict.reasons.iter().any(|reason| reason.starts_with("publication 1, row 4:")));
+        assert!(verdict.reasons.iter().all(|reason| !reason.starts_with("publication 2")));
+
+        //Without publications nothing opens, so nothing is certified
+        assert!(!Auditor::new(1, 4).audit::<Fq>(&pki, &[], &[Fq::from(5u64)]).unwrap().passed);
+    }
+}
@@ -0,0 +1,198 @@
+#[cfg(feature = "parallel")]
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "parallel")]
+use ark_bls12_381::Fq;
+use ark_ff::Field;
+#[cfg(feature = "parallel")]
+use rayon::ThreadPoolBuilder;
+
+#[cfg(feature = "parallel")]
+use crate::rand_extr::{ExtractorParams, RandomnessExtractor};
+use crate::metrics::{RoleCost, Role};
+use crate::vss::{Authentication, PubParams, VssReport, VSS};
+use crate::error::Result;
+
+///Names of the per-run VSS metrics, in the order of `Metric`
+pub const VSS_METRICS: [&str; 11] = [
+    "dealer_time_ms",
+    "receiver_time_ms",
+    "client_time_ms",
+    "dealer_comm_mb",
+    "receiver_comm_mb",
+    "reconstructor_comm_mb",
+    "overall_time_ms",
+    "overall_comm_mb",
+    "dealer_time_per_secret_ms",
+    "overall_time_per_secret_ms",
+    "overall_comm_per_secret_mb",
+];
+
+pub const N_METRICS: usize = VSS_METRICS.len();
+
+///Position of each VSS metric in `VSS_METRICS` and in a sweep row
+#[derive(Clone, Copy)]
+pub enum Metric {
+    DealerTime,
+    ReceiverTime,
+    ClientTime,
+    DealerComm,
+    ReceiverComm,
+    ReconstructorComm,
+    OverallTime,
+    OverallComm,
+    DealerTimePerSecret,
+    OverallTimePerSecret,
+    OverallCommPerSecret,
+}
+
+///Mean and standard deviation of every VSS metric for one t
+pub struct SweepRow {
+    pub t: u64,
+    pub packing: u64,
+    pub repetitions: usize,
+    pub mean: [f64; N_METRICS],
+    pub stddev: [f64; N_METRICS],
+}
+
+impl SweepRow {
+    pub fn mean(&self, metric: Metric) -> f64 {
+        self.mean[metric as usize]
+    }
+
+    pub fn stddev(&self, metric: Metric) -> f64 {
+        self.stddev[metric as usize]
+    }
+}
+
+fn time_ms(cost: &RoleCost) -> f64 {
+    cost.time.as_secs_f64() * 1000.0
+}
+
+fn comm_mb(cost: &RoleCost) -> f64 {
+    (cost.bytes as f64) / 1000000.0
+}
+
+//Receiver and reconstructor figures are averaged over the parties in that role, per-secret figures over the packed secrets
+fn vss_metrics<F: Field>(report: &VssReport<F>) -> [f64; N_METRICS] {
+    let receivers = report.costs.by_role(Role::Receiver);
+    let reconstructors = report.costs.by_role(Role::Reconstructor);
+    let dealer = report.costs.get(0, Role::Dealer);
+    let client = report.costs.by_role(Role::Client);
+    let total = report.costs.total();
+    let secrets = report.secrets.len() as f64;
+    let mut metrics = [0.0; N_METRICS];
+    metrics[Metric::DealerTime as usize] = time_ms(&dealer);
+    metrics[Metric::ReceiverTime as usize] = receivers.iter().map(time_ms).sum::<f64>() / receivers.len() as f64;
+    metrics[Metric::ClientTime as usize] = client.iter().map(time_ms).sum::<f64>();
+    metrics[Metric::DealerComm as usize] = comm_mb(&dealer);
+    metrics[Metric::ReceiverComm as usize] = receivers.iter().map(comm_mb).sum::<f64>() / receivers.len() as f64;
+    metrics[Metric::ReconstructorComm as usize] = reconstructors.iter().map(comm_mb).sum::<f64>() / reconstructors.len() as f64;
+    metrics[Metric::OverallTime as usize] = time_ms(&total);
+    metrics[Metric::OverallComm as usize] = comm_mb(&total);
+    metrics[Metric::DealerTimePerSecret as usize] = time_ms(&dealer) / secrets;
+    metrics[Metric::OverallTimePerSecret as usize] = time_ms(&total) / secrets;
+    metrics[Metric::OverallCommPerSecret as usize] = comm_mb(&total) / secrets;
+    metrics
+}
+
+//Sample mean and standard deviation per metric
+fn mean_and_stddev(samples: &[[f64; N_METRICS]]) -> ([f64; N_METRICS], [f64; N_METRICS]) {
+    let count = samples.len() as f64;
+    let mut mean = [0.0; N_METRICS];
+    let mut stddev = [0.0; N_METRICS];
+    for metric in 0..N_METRICS {
+        mean[metric] = samples.iter().map(|sample| sample[metric]).sum::<f64>() / count;
+        if samples.len() > 1 {
+            let variance = samples.iter().map(|sample| (sample[metric] - mean[metric]).powi(2)).sum::<f64>() / (count - 1.0);
+            stddev[metric] = variance.sqrt();
+        }
+    }
+    (mean, stddev)
+}
+
+///Runs the VSS `repetitions` times for every t and aggregates the metrics `VSS::execute` computes.
+///Each dealing packs `packing` secrets, with the receivers of PubParams::packed; run r draws its keys and dealer from seed + r.
+pub fn sweep_vss<F: Field>(ts: impl Iterator<Item = u64>, repetitions: usize, seed: u64, authentication: Authentication, packing: u64) -> Result<Vec<SweepRow>> {
+    ts.map(|t| {
+            let samples: Vec<[f64; N_METRICS]> = (0..repetitions as u64)
+                .map(|repetition| {
+                    let (n, n_parties_total) = PubParams::packed_size(t, packing);
+                    let pp = PubParams::with_size_and_seed(t, n, n_parties_total, seed + repetition)?.with_authentication(authentication).with_packing(packing);
+                    let vss: VSS<F> = VSS { secrets: (1..=packing).map(F::from).collect(), pp: pp, execution_leaks: false, seed: seed + repetition };
+                    Ok(vss_metrics(&vss.execute()?))
+                })
+                .collect::<Result<Vec<[f64; N_METRICS]>>>()?;
+            let (mean, stddev) = mean_and_stddev(&samples);
+            Ok(SweepRow { t: t, packing: packing, repetitions: repetitions, mean: mean, stddev: stddev })
+        })
+        .collect()
+}
+
+///One line per t with the mean and standard deviation of every metric
+pub fn sweep_csv(rows: &[SweepRow]) -> String {
+    let mut csv = String::from("t,packing,repetitions");
+    for metric in VSS_METRICS {
+        csv.push_str(&format!(",{}_mean,{}_stddev", metric, metric));
+    }
+    csv.push('\n');
+    for row in rows {
+        csv.push_str(&format!("{},{},{}", row.t, row.packing, row.repetitions));
+        for metric in 0..N_METRICS {
+            csv.push_str(&format!(",{},{}", row.mean[metric], row.stddev[metric]));
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+//Thread counts 1, 2, 4, ... up to the number of available cores
+#[cfg(feature = "parallel")]
+fn thread_counts() -> Vec<usize> {
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut counts: Vec<usize> = Vec::new();
+    let mut threads = 1;
+    while threads < max_threads {
+        counts.push(threads);
+        threads *= 2;
+    }
+    counts.push(max_threads);
+    counts
+}
+
+//Time `run` takes on a pool of `threads` threads, or its error
+#[cfg(feature = "parallel")]
+fn time_in_pool<T: Send, G: FnOnce() -> Result<T> + Send>(threads: usize, run: G) -> Result<Duration> {
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+    let start_time = SystemTime::now();
+    pool.install(run)?;
+    Ok(SystemTime::now().duration_since(start_time).unwrap())
+}
+
+#[cfg(feature = "parallel")]
+///Runs the VSS and the randomness extractor on thread pools of increasing size and prints the speedup over one thread
+pub fn thread_scaling(pp: &PubParams, params: &ExtractorParams, seed: u64) -> Result<()> {
+    let mut rows: Vec<(usize, Duration, Duration)> = Vec::new();
+
+    for threads in thread_counts() {
+        let vss: VSS<Fq> = VSS { secrets: (1..=pp.packing).map(Fq::from).collect(), pp: PubParams::with_size_and_seed(pp.t, pp.n, pp.n_parties_total, seed)?.with_authentication(pp.authentication).with_packing(pp.packing), execution_leaks: false, seed: seed };
+        let vss_time = time_in_pool(threads, || vss.execute())?;
+
+        let rand_extr = RandomnessExtractor { params: params.clone(), seed: Some(seed) };
+        let extractor_time = time_in_pool(threads, || rand_extr.execute())?;
+
+        rows.push((threads, vss_time, extractor_time));
+    }
+
+    let (_, vss_baseline, extractor_baseline) = rows[0];
+    println!("threads | VSS ms | VSS speedup | extractor ms | extractor speedup");
+    for (threads, vss_time, extractor_time) in rows {
+        println!("{} | {} | {:.2} | {} | {:.2}",
+                    threads,
+                    vss_time.as_millis(),
+                    vss_baseline.as_secs_f64() / vss_time.as_secs_f64(),
+                    extractor_time.as_millis(),
+                    extractor_baseline.as_secs_f64() / extractor_time.as_secs_f64());
+    }
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::communication::{BroadcastChannel, BroadcastMessage, BulletinBoard};
+use crate::error::{Error, Result};
+
+//The board is a file of records, one per store_round call, each laid out as
+//round | hash of the previous record | number of messages | (sender | payload length | payload)* | hash,
+//with integers as u64 LE. A record's hash covers everything before it in the record, so the last hash
+//commits to the whole board; the first record chains to 32 zero bytes.
+
+const GENESIS: [u8; 32] = [0u8; 32];
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+///Position in a board that later readers can verify from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Checkpoint {
+    //Number of records up to and including the checkpoint
+    pub height: u64,
+    //Hash of the last of those records
+    pub head: [u8; 32],
+}
+
+impl Checkpoint {
+    ///The empty board
+    pub fn genesis() -> Checkpoint {
+        Checkpoint { height: 0, head: GENESIS }
+    }
+
+    pub fn head_hex(&self) -> String {
+        to_hex(&self.head)
+    }
+
+    pub fn from_hex(height: u64, head: &str) -> Result<Checkpoint> {
+        let invalid = || Error::InvalidParams(format!("{} is not a 32-byte hex hash", head));
+        if head.len() != 64 {
+            return Err(invalid());
+        }
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(head.get(2*i..2*i + 2).ok_or_else(invalid)?, 16).map_err(|_| invalid())?;
+        }
+        Ok(Checkpoint { height: height, head: bytes })
+    }
+}
+
+struct Record {
+    round: u64,
+    prev: [u8; 32],
+    messages: Vec<BroadcastMessage>,
+    hash: [u8; 32],
+}
+
+impl Record {
+    fn new(round: u64, prev: [u8; 32], messages: &[BroadcastMessage]) -> Record {
+        let mut record = Record { round: round, prev: prev, messages: messages.to_vec(), hash: GENESIS };
+        record.hash = Sha256::digest(record.body()).into();
+        record
+    }
+
+    //Everything the hash covers
+    fn body(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.round.to_le_bytes());
+        bytes.extend_from_slice(&self.prev);
+        bytes.extend_from_slice(&(self.messages.len() as u64).to_le_bytes());
+        for message in &self.messages {
+            bytes.extend_from_slice(&message.sender().to_le_bytes());
+            bytes.extend_from_slice(&(message.payload().len() as u64).to_le_bytes());
+            bytes.extend_from_slice(message.payload());
+        }
+        bytes
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.body();
+        bytes.extend_from_slice(&self.hash);
+        bytes
+    }
+
+    //Parses the record starting at `bytes[*pos]`; `index` only labels errors
+    fn parse(bytes: &[u8], pos: &mut usize, index: u64) -> Result<Record> {
+        let mut take = |len: usize| -> Result<&[u8]> {
+            let end = pos.checked_add(len).ok_or_else(|| Error::BrokenBoard { record: index, reason: "truncated".to_string() })?;
+            let chunk = bytes.get(*pos..end).ok_or_else(|| Error::BrokenBoard { record: index, reason: "truncated".to_string() })?;
+            *pos = end;
+            Ok(chunk)
+        };
+        let round = u64::from_le_bytes(take(8)?.try_into().unwrap());
+        let prev: [u8; 32] = take(32)?.try_into().unwrap();
+        let count = u64::from_le_bytes(take(8)?.try_into().unwrap());
+        let mut messages = Vec::new();
+        for _ in 0..count {
+            let sender = u64::from_le_bytes(take(8)?.try_into().unwrap());
+            let len = u64::from_le_bytes(take(8)?.try_into().unwrap());
+            let len = usize::try_from(len).map_err(|_| Error::BrokenBoard { record: index, reason: "payload too long".to_string() })?;
+            messages.push(BroadcastMessage::new(sender, take(len)?.to_vec()));
+        }
+        let hash: [u8; 32] = take(32)?.try_into().unwrap();
+        Ok(Record { round: round, prev: prev, messages: messages, hash: hash })
+    }
+}
+
+//Reads the board at `path` and checks the chain from `from` on: the record at the checkpoint must have its
+//hash, and every later record must chain to its predecessor and hash correctly. Earlier records are only parsed.
+fn read_chain(path: &Path, from: &Checkpoint) -> Result<(Vec<Record>, Checkpoint)> {
+    let mut bytes = Vec::new();
+    match File::open(path) {
+        Ok(mut file) => { file.read_to_end(&mut bytes)?; },
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+        Err(error) => return Err(error.into()),
+    }
+    let mut records = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        records.push(Record::parse(&bytes, &mut pos, records.len() as u64)?);
+    }
+    if (records.len() as u64) < from.height {
+        return Err(Error::BrokenBoard { record: records.len() as u64, reason: format!("board ends before checkpoint at height {}", from.height) });
+    }
+
+    let mut head = from.head;
+    if from.height > 0 && records[from.height as usize - 1].hash != head {
+        return Err(Error::BrokenBoard { record: from.height - 1, reason: "hash differs from the checkpoint".to_string() });
+    }
+    for (index, record) in records.iter().enumerate().skip(from.height as usize) {
+        if record.prev != head {
+            return Err(Error::BrokenBoard { record: index as u64, reason: "does not chain to the previous record".to_string() });
+        }
+        if <[u8; 32]>::from(Sha256::digest(record.body())) != record.hash {
+            return Err(Error::BrokenBoard { record: index as u64, reason: "hash does not match its contents".to_string() });
+        }
+        head = record.hash;
+    }
+    let checkpoint = Checkpoint { height: records.len() as u64, head: records.last().map(|record| record.hash).unwrap_or(GENESIS) };
+    Ok((records, checkpoint))
+}
+
+///Append-only bulletin board persisted to a file, with every round hash-chained to the ones before it
+pub struct FileBoard {
+    file: File,
+    rounds: BroadcastChannel,
+    head: Checkpoint,
+}
+
+impl FileBoard {
+    ///Opens the board at `path`, creating it if needed, after verifying its whole chain
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileBoard> {
+        let path = path.as_ref();
+        let (records, head) = read_chain(path, &Checkpoint::genesis())?;
+        let mut rounds = BroadcastChannel::default();
+        for record in &records {
+            rounds.store_round(record.round, &record.messages)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileBoard { file: file, rounds: rounds, head: head })
+    }
+
+    ///Verifies the board at `path` from `from` on, without trusting anything before it; returns the current head
+    pub fn verify_from<P: AsRef<Path>>(path: P, from: &Checkpoint) -> Result<Checkpoint> {
+        Ok(read_chain(path.as_ref(), from)?.1)
+    }
+
+    ///Current head, to hand to later readers
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.head
+    }
+
+    ///Highest round posted so far
+    pub fn last_round(&self) -> Option<u64> {
+        self.rounds.rounds().last().copied()
+    }
+}
+
+impl BulletinBoard for FileBoard {
+    ///Appends the messages as one record and syncs it to disk before updating the in-memory rounds
+    fn store_round(&mut self, round: u64, messages: &[BroadcastMessage]) -> Result<()> {
+        let record = Record::new(round, self.head.head, messages);
+        self.file.write_all(&record.to_bytes())?;
+        self.file.sync_data()?;
+        self.head = Checkpoint { height: self.head.height + 1, head: record.hash };
+        self.rounds.store_round(round, messages)
+    }
+
+    fn read_round(&self, round: u64) -> Option<&Vec<BroadcastMessage>> {
+        self.rounds.read_round(round)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    //A board file of its own per test, holding rounds 0 and 1
+    fn board(name: &str) -> (PathBuf, Checkpoint) {
+        let path = std::env::temp_dir().join(format!("yosowcc-board-test-{}-{}.bin", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        let mut board = FileBoard::open(&path).unwrap();
+        board.store_round(0, &[BroadcastMessage::new(1, vec![1, 2, 3])]).unwrap();
+        let first = board.checkpoint();
+        board.store_round(1, &[BroadcastMessage::new(2, vec![4, 5]), BroadcastMessage::new(3, vec![])]).unwrap();
+        (path, first)
+    }
+
+    fn broken_record(result: Result<Checkpoint>) -> u64 {
+        match result {
+            Err(Error::BrokenBoard { record, .. }) => record,
+            other => panic!("expected a broken board, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn a_board_reopens_and_verifies_from_any_checkpoint() {
+        let (path, first) = board("reopen");
+        let head = FileBoard::verify_from(&path, &Checkpoint::genesis()).unwrap();
+        assert_eq!(head.height, 2);
+        assert_eq!(FileBoard::verify_from(&path, &first).unwrap(), head);
+        let reopened = FileBoard::open(&path).unwrap();
+        assert_eq!(reopened.checkpoint(), head);
+        assert_eq!(reopened.last_round(), Some(1));
+        assert_eq!(reopened.read_round(1).unwrap().len(), 2);
+        assert_eq!(Checkpoint::from_hex(head.height, &head.head_hex()).unwrap(), head);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn tampering_breaks_the_chain_where_it_happened() {
+        let (path, first) = board("tamper");
+        let honest = std::fs::read(&path).unwrap();
+
+        //A changed payload byte no longer matches its record's hash
+        let mut bytes = honest.clone();
+        bytes[8 + 32 + 8 + 16] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(broken_record(FileBoard::verify_from(&path, &Checkpoint::genesis())), 0);
+
+        //Rehashing the changed record moves the break to the next record, which chains to the old hash
+        let mut pos = 0;
+        let mut record = Record::parse(&bytes, &mut pos, 0).unwrap();
+        record.hash = Sha256::digest(record.body()).into();
+        let mut rewritten = record.to_bytes();
+        rewritten.extend_from_slice(&honest[pos..]);
+        std::fs::write(&path, &rewritten).unwrap();
+        assert_eq!(broken_record(FileBoard::verify_from(&path, &Checkpoint::genesis())), 1);
+        //A reader holding the first checkpoint spots the rewrite at once
+        assert_eq!(broken_record(FileBoard::verify_from(&path, &first)), 0);
+
+        std::fs::write(&path, &honest[..honest.len() - 1]).unwrap();
+        assert_eq!(broken_record(FileBoard::verify_from(&path, &Checkpoint::genesis())), 1);
+        assert!(FileBoard::open(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
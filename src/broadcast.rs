@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::adversary::Adversary;
+use crate::communication::{BroadcastChannel, BroadcastMessage, BulletinBoard};
+use crate::error::{Error, Result};
+
+//Bracha's reliable broadcast over point-to-point channels, for n >= 3t+1 parties numbered 1..=n.
+//The sender sends its message to everybody; a party echoes the first message it gets from the sender,
+//gets ready for a value once ceil((n+t+1)/2) parties echoed it or t+1 parties are ready for it,
+//and delivers once 2t+1 parties are ready for it. Two honest parties never deliver different values,
+//and if one of them delivers everybody does, so all honest parties read the same board.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Step {
+    Send,
+    Echo,
+    Ready,
+}
+
+//What one honest party saw in one broadcast instance
+#[derive(Default)]
+struct InstanceState {
+    echoed: bool,
+    ready: bool,
+    echoes: BTreeMap<Vec<u8>, BTreeSet<u64>>,
+    readies: BTreeMap<Vec<u8>, BTreeSet<u64>>,
+    delivered: Option<Vec<u8>>,
+}
+
+///Bulletin board built from reliable broadcast; every honest party keeps its own view of it
+pub struct ReliableBroadcast<'a, A: Adversary> {
+    pub n: u64,
+    pub t: u64,
+    adversary: &'a mut A,
+    views: BTreeMap<u64, BroadcastChannel>,
+    //Honest party whose view read_round returns
+    pub reader: u64,
+    //Point-to-point messages sent so far
+    pub messages: u64,
+}
+
+impl<'a, A: Adversary> ReliableBroadcast<'a, A> {
+    ///Parties `adversary` corrupts equivocate as senders and vote for every value they see
+    pub fn new(n: u64, t: u64, adversary: &'a mut A) -> Result<ReliableBroadcast<'a, A>> {
+        if n < 3*t + 1 {
+            return Err(Error::InvalidParams(format!("reliable broadcast among {} parties cannot tolerate {} corruptions", n, t)));
+        }
+        let views: BTreeMap<u64, BroadcastChannel> = (1..=n)
+            .filter(|party| !adversary.is_corrupted(*party))
+            .map(|party| (party, BroadcastChannel::default()))
+            .collect();
+        if views.len() as u64 + t < n {
+            return Err(Error::InvalidParams(format!("more than {} of {} parties are corrupted", t, n)));
+        }
+        let reader = *views.keys().next().ok_or_else(|| Error::InvalidParams("no honest party to read the board".to_string()))?;
+        Ok(ReliableBroadcast { n: n, t: t, adversary: adversary, views: views, reader: reader, messages: 0 })
+    }
+
+    ///The board as each honest party reads it
+    pub fn views(&self) -> &BTreeMap<u64, BroadcastChannel> {
+        &self.views
+    }
+
+    //Runs one instance in which party p first receives sent[p] from the sender; returns what each honest party delivered
+    fn run_instance(&mut self, sender: u64, sent: &BTreeMap<u64, Vec<u8>>) -> BTreeMap<u64, Vec<u8>> {
+        let n = self.n;
+        let t = self.t;
+        let echo_threshold = (n + t + 2) / 2;
+        let mut states: BTreeMap<u64, InstanceState> = self.views.keys().map(|party| (*party, InstanceState::default())).collect();
+        let mut queue: VecDeque<(Step, u64, u64, Vec<u8>)> = VecDeque::new();
+
+        for (to, payload) in sent {
+            queue.push_back((Step::Send, sender, *to, payload.clone()));
+        }
+        //Corrupted parties echo and get ready for every value the sender sent to anybody
+        let values: BTreeSet<&Vec<u8>> = sent.values().collect();
+        for from in (1..=n).filter(|party| !self.views.contains_key(party)) {
+            for value in &values {
+                for to in 1..=n {
+                    queue.push_back((Step::Echo, from, to, (*value).clone()));
+                    queue.push_back((Step::Ready, from, to, (*value).clone()));
+                }
+            }
+        }
+
+        while let Some((step, from, to, payload)) = queue.pop_front() {
+            self.messages += 1;
+            //Corrupted parties do not need to be told anything
+            let state = match states.get_mut(&to) {
+                Some(state) => state,
+                None => continue,
+            };
+            let send_ready = match step {
+                Step::Send => {
+                    if from == sender && !state.echoed {
+                        state.echoed = true;
+                        for recipient in 1..=n {
+                            queue.push_back((Step::Echo, to, recipient, payload.clone()));
+                        }
+                    }
+                    false
+                },
+                Step::Echo => {
+                    let echoes = state.echoes.entry(payload.clone()).or_default();
+                    echoes.insert(from);
+                    echoes.len() as u64 >= echo_threshold
+                },
+                Step::Ready => {
+                    let readies = state.readies.entry(payload.clone()).or_default();
+                    readies.insert(from);
+                    let count = readies.len() as u64;
+                    if count > 2*t && state.delivered.is_none() {
+                        state.delivered = Some(payload.clone());
+                    }
+                    count > t
+                },
+            };
+            if send_ready && !state.ready {
+                state.ready = true;
+                for recipient in 1..=n {
+                    queue.push_back((Step::Ready, to, recipient, payload.clone()));
+                }
+            }
+        }
+
+        states.into_iter().filter_map(|(party, state)| state.delivered.map(|payload| (party, payload))).collect()
+    }
+}
+
+impl<A: Adversary> BulletinBoard for ReliableBroadcast<'_, A> {
+    ///Broadcasts each message in turn; a corrupted sender may send different payloads to different parties
+    fn store_round(&mut self, round: u64, messages: &[BroadcastMessage]) -> Result<()> {
+        for message in messages {
+            let sender = message.sender();
+            let mut sent: BTreeMap<u64, Vec<u8>> = BTreeMap::new();
+            for to in 1..=self.n {
+                let mut payload = message.payload().to_vec();
+                if self.adversary.is_corrupted(sender) {
+                    self.adversary.tamper_broadcast(sender, to, &mut payload);
+                }
+                sent.insert(to, payload);
+            }
+            for (party, payload) in self.run_instance(sender, &sent) {
+                if let Some(view) = self.views.get_mut(&party) {
+                    view.store_round(round, &[BroadcastMessage::new(sender, payload)])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_round(&self, round: u64) -> Option<&Vec<BroadcastMessage>> {
+        self.views.get(&self.reader)?.read_round(round)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adversary::{Behavior, Corruption, Honest};
+
+    use super::*;
+
+    //What each honest party read in round 0
+    fn round_zero<A: Adversary>(broadcast: &ReliableBroadcast<A>) -> Vec<Option<Vec<BroadcastMessage>>> {
+        broadcast.views().values().map(|view| view.read_round(0).cloned()).collect()
+    }
+
+    #[test]
+    fn every_honest_party_delivers_an_honest_senders_message_despite_t_faulty_parties() {
+        let mut corruption = Corruption::new(2).corrupt(6, Behavior::Silent).unwrap().corrupt(7, Behavior::Equivocate).unwrap();
+        let mut broadcast = ReliableBroadcast::new(7, 2, &mut corruption).unwrap();
+        let message = BroadcastMessage::new(1, vec![7, 8, 9]);
+        broadcast.store_round(0, std::slice::from_ref(&message)).unwrap();
+        assert_eq!(broadcast.views().len(), 5);
+        assert!(round_zero(&broadcast).into_iter().all(|read| read == Some(vec![message.clone()])));
+        assert_eq!(broadcast.read_round(0), Some(&vec![message]));
+    }
+
+    #[test]
+    fn an_equivocating_sender_cannot_split_the_honest_parties() {
+        for sender in 1..=4 {
+            let mut corruption = Corruption::new(1).corrupt(sender, Behavior::Equivocate).unwrap();
+            let mut broadcast = ReliableBroadcast::new(4, 1, &mut corruption).unwrap();
+            broadcast.store_round(0, &[BroadcastMessage::new(sender, vec![0, 1])]).unwrap();
+            let reads = round_zero(&broadcast);
+            assert!(reads.windows(2).all(|pair| pair[0] == pair[1]), "sender {}", sender);
+        }
+    }
+
+    #[test]
+    fn too_many_corruptions_are_rejected() {
+        assert!(ReliableBroadcast::new(6, 2, &mut Honest).is_err());
+        let mut corruption = Corruption::new(2).corrupt(1, Behavior::Silent).unwrap().corrupt(2, Behavior::Silent).unwrap();
+        assert!(ReliableBroadcast::new(4, 1, &mut corruption).is_err());
+    }
+}
@@ -0,0 +1,795 @@
+use ark_bls12_381::{Fq, Fr};
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::json;
+
+use crate::rand_extr::{ExtractorParams, LeaderRule, RandomnessExtractor};
+use crate::subset_family::{sampled_family_size, SubsetFamily};
+use crate::bench::{sweep_csv, sweep_vss, Metric};
+use crate::board::{Checkpoint, FileBoard};
+use crate::communication::{BroadcastMessage, BulletinBoard};
+use crate::error::Error;
+use crate::metrics::CostReport;
+use crate::adversary::Honest;
+use crate::auditor::{Auditor, Verdict};
+use crate::reshare::{Resharing, ResharingOutcome};
+use crate::dkg::{Dkg, DkgOutcome};
+use crate::board::to_hex;
+use crate::scenarios;
+use crate::transcript::{Protocol, Transcript, Verification};
+use crate::vss::{claimed_output, display_values, publications_of, Authentication, BeaconOutcome, PubParams, RandExtractorVSSBased, VssReport, VSS};
+
+//Seed the dealer always used before it became configurable
+const DEFAULT_DEALER_SEED: u64 = 6;
+//Statistical security of sampled subset families, in bits
+const SAMPLED_FAMILY_SECURITY: u32 = 40;
+
+#[derive(Parser)]
+#[command(about = "YOSO verifiable secret sharing and randomness beacon experiments")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    ///How results are printed
+    #[arg(long, value_enum, global = true, default_value_t = Output::Human)]
+    pub output: Output,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    ///Share a secret with the YOSO VSS and reconstruct it
+    Vss(VssArgs),
+    ///Run the combinatorial randomness extractor
+    Extract(ExtractArgs),
+    ///Run the VSS-based randomness beacon
+    Beacon(BeaconArgs),
+    ///Share secrets, pass them on through fresh committees and open them with the last one
+    Reshare(ReshareArgs),
+    ///Generate a threshold key with the public key in G1 of BLS12-381; the field is always its scalar field
+    Dkg(DkgArgs),
+    ///Sweep the VSS over a range of t and write mean and standard deviation as CSV
+    Bench(BenchArgs),
+    ///Measure the speedup of the VSS and the extractor versus the number of threads (needs the `parallel` feature)
+    ThreadScaling(ThreadScalingArgs),
+    ///Run the fault-injection scenarios against both protocols
+    Scenarios(ScenarioArgs),
+    ///Verify the hash chain of an archived bulletin board
+    Audit(AuditArgs),
+    ///Replay a recorded transcript, flag deviations from the protocol and recompute its output
+    VerifyTranscript(VerifyTranscriptArgs),
+    ///Audit the output of a VSS or beacon transcript from the role keys and publications alone
+    Certify(CertifyArgs),
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Output {
+    Human,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FieldChoice {
+    ///Base field of BLS12-381
+    Fq,
+    ///Scalar field of BLS12-381
+    Fr,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SignatureScheme {
+    ///An ed25519 signature on every subshare
+    Ed25519,
+    ///ed25519 signatures on Merkle roots only, subshares carry authentication paths
+    Ed25519Merkle,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LeaderRuleChoice {
+    LowestIndex,
+    RoundRobin,
+    Hashed,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FamilyChoice {
+    ///All k-subsets
+    Complete,
+    ///Covering design, greedy or built from groups of parties
+    Covering,
+    ///Randomly sampled subsets
+    Sampled,
+}
+
+///Committee of a VSS-based protocol
+#[derive(Args)]
+pub struct CommitteeArgs {
+    ///Number of potentially adversarial parties
+    #[arg(short, long, default_value_t = 7)]
+    pub t: u64,
+    ///Number of receivers, 3t+packing by default
+    #[arg(short, long)]
+    pub n: Option<u64>,
+    ///Total number of parties, 5t+3+packing by default
+    #[arg(long)]
+    pub n_parties_total: Option<u64>,
+    ///Seed for the parties' keys, the nominated role keys and the protocol randomness; fresh randomness if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+    #[arg(long, value_enum, default_value_t = SignatureScheme::Ed25519)]
+    pub signature_scheme: SignatureScheme,
+}
+
+impl CommitteeArgs {
+    pub fn pub_params(&self, packing: u64) -> Result<PubParams, String> {
+        let (n, n_parties_total) = PubParams::packed_size(self.t, packing);
+        let (n, n_parties_total) = (self.n.unwrap_or(n), self.n_parties_total.unwrap_or(n_parties_total));
+        let pp = match self.seed {
+            Some(seed) => PubParams::with_size_and_seed(self.t, n, n_parties_total, seed)?,
+            None => PubParams::with_size(self.t, n, n_parties_total)?,
+        };
+        Ok(pp.with_authentication(authentication(self.signature_scheme)).with_packing(packing))
+    }
+}
+
+fn authentication(signature_scheme: SignatureScheme) -> Authentication {
+    match signature_scheme {
+        SignatureScheme::Ed25519 => Authentication::Signatures,
+        SignatureScheme::Ed25519Merkle => Authentication::Merkle,
+    }
+}
+
+///Committee, packing and field of the VSS, the beacon and resharing
+#[derive(Args)]
+pub struct ProtocolArgs {
+    #[command(flatten)]
+    pub committee: CommitteeArgs,
+    ///Secrets packed into each VSS dealing
+    #[arg(long, default_value_t = 1)]
+    pub packing: u64,
+    #[arg(long, value_enum, default_value_t = FieldChoice::Fq)]
+    pub field: FieldChoice,
+}
+
+impl ProtocolArgs {
+    pub fn pub_params(&self) -> Result<PubParams, String> {
+        self.committee.pub_params(self.packing)
+    }
+}
+
+#[derive(Args)]
+pub struct VssArgs {
+    #[command(flatten)]
+    pub protocol: ProtocolArgs,
+    ///Secrets to share, one per packed slot
+    #[arg(long, num_args = 1.., default_values_t = [1])]
+    pub secret: Vec<u64>,
+    ///Leak each role's state to the adversary after it speaks
+    #[arg(long)]
+    pub execution_leaks: bool,
+    ///Record every message into this transcript file
+    #[arg(long)]
+    pub transcript: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ReshareArgs {
+    #[command(flatten)]
+    pub protocol: ProtocolArgs,
+    ///Secrets to share, one per packed slot
+    #[arg(long, num_args = 1.., default_values_t = [1])]
+    pub secret: Vec<u64>,
+    ///Number of times the secrets are passed to a fresh committee
+    #[arg(long, default_value_t = 2)]
+    pub epochs: u64,
+}
+
+#[derive(Args)]
+pub struct DkgArgs {
+    #[command(flatten)]
+    pub committee: CommitteeArgs,
+}
+
+#[derive(Args)]
+pub struct ScenarioArgs {
+    ///Number of potentially adversarial parties
+    #[arg(short, long, default_value_t = 1)]
+    pub t: u64,
+    #[arg(long, default_value_t = DEFAULT_DEALER_SEED)]
+    pub seed: u64,
+}
+
+#[derive(Args)]
+pub struct BeaconArgs {
+    #[command(flatten)]
+    pub protocol: ProtocolArgs,
+    ///Leak each role's state to the adversary after it speaks
+    #[arg(long)]
+    pub execution_leaks: bool,
+    ///Append the beacon output as a new round to this bulletin board file
+    #[arg(long)]
+    pub archive: Option<PathBuf>,
+    ///Record every message of every dealing into this transcript file
+    #[arg(long)]
+    pub transcript: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct VerifyTranscriptArgs {
+    ///Transcript file written by vss, beacon or extract
+    pub transcript: PathBuf,
+    ///Field the recorded run used
+    #[arg(long, value_enum, default_value_t = FieldChoice::Fq)]
+    pub field: FieldChoice,
+}
+
+#[derive(Args)]
+pub struct AuditArgs {
+    ///Bulletin board file to verify
+    pub board: PathBuf,
+    ///Verify from the checkpoint with this many records instead of from the start
+    #[arg(long, requires = "head")]
+    pub height: Option<u64>,
+    ///Head hash of that checkpoint, in hex
+    #[arg(long, requires = "height")]
+    pub head: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CertifyArgs {
+    #[command(flatten)]
+    pub transcript: VerifyTranscriptArgs,
+    ///Write the certificate to this file as JSON
+    #[arg(long)]
+    pub certificate: Option<PathBuf>,
+}
+
+///Subset family of the extractor
+#[derive(Args)]
+pub struct FamilyArgs {
+    ///Subset size, 2t+1 by default
+    #[arg(short, long)]
+    pub k: Option<usize>,
+    #[arg(long, value_enum, default_value_t = LeaderRuleChoice::LowestIndex)]
+    pub leader_rule: LeaderRuleChoice,
+    #[arg(long, value_enum, default_value_t = FamilyChoice::Complete)]
+    pub family: FamilyChoice,
+    ///Number of sampled subsets, by default enough for 40 bits of statistical security
+    #[arg(long)]
+    pub family_size: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct ExtractArgs {
+    ///Number of potentially adversarial parties
+    #[arg(short, long, default_value_t = 7)]
+    pub t: u64,
+    ///Number of verifiers, 3t+1 by default
+    #[arg(short, long)]
+    pub n: Option<u64>,
+    ///Seed for the subsets' random values and a sampled family
+    #[arg(long)]
+    pub seed: Option<u64>,
+    #[command(flatten)]
+    pub family: FamilyArgs,
+    ///Record every message into this transcript file
+    #[arg(long)]
+    pub transcript: Option<PathBuf>,
+    ///Write the per-party costs to this file as CSV
+    #[arg(long)]
+    pub costs_csv: Option<PathBuf>,
+}
+
+impl FamilyArgs {
+    ///Family for t corruptions among n verifiers, 3t+1 if not given; `seed` samples a sampled family
+    pub fn extractor_params(&self, t: u64, n: Option<u64>, seed: Option<u64>) -> Result<ExtractorParams, String> {
+        let t = t as usize;
+        let default = ExtractorParams::new(t);
+        let n = n.map_or(default.n, |n| n as usize);
+        let k = self.k.unwrap_or(default.k);
+        let leader_rule = match self.leader_rule {
+            LeaderRuleChoice::LowestIndex => LeaderRule::LowestIndex,
+            LeaderRuleChoice::RoundRobin => LeaderRule::RoundRobin,
+            LeaderRuleChoice::Hashed => LeaderRule::Hashed,
+        };
+        let family = match self.family {
+            FamilyChoice::Complete => SubsetFamily::Complete,
+            FamilyChoice::Covering => SubsetFamily::covering_design(n, k, t)?,
+            FamilyChoice::Sampled => {
+                let m = match self.family_size {
+                    Some(m) => m,
+                    None => sampled_family_size(n, k, t, SAMPLED_FAMILY_SECURITY)
+                                .ok_or(format!("no {}-subset of {} parties survives {} corruptions", k, n, t))?,
+                };
+                SubsetFamily::sampled(n, k, m, seed.unwrap_or(0))
+            }
+        };
+        Ok(ExtractorParams { n: n, k: k, t: t, leader_rule: leader_rule, family: family })
+    }
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    ///Smallest t of the sweep
+    #[arg(long, default_value_t = 1)]
+    pub t_from: u64,
+    ///Largest t of the sweep
+    #[arg(long, default_value_t = 7)]
+    pub t_to: u64,
+    #[arg(long, default_value_t = 1)]
+    pub t_step: u64,
+    ///Runs per t
+    #[arg(long, default_value_t = 5)]
+    pub repetitions: usize,
+    ///Write the CSV to this file instead of stdout
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+    ///Secrets packed into each dealing
+    #[arg(long, default_value_t = 1)]
+    pub packing: u64,
+    ///Seed of the first run's keys and dealer; run r uses seed + r
+    #[arg(long, default_value_t = DEFAULT_DEALER_SEED)]
+    pub seed: u64,
+    #[arg(long, value_enum, default_value_t = SignatureScheme::Ed25519)]
+    pub signature_scheme: SignatureScheme,
+    #[arg(long, value_enum, default_value_t = FieldChoice::Fq)]
+    pub field: FieldChoice,
+}
+
+///The VSS runs on BLS12-381's base field; -t, -n and the seed apply to the extractor as well
+#[derive(Args)]
+pub struct ThreadScalingArgs {
+    #[command(flatten)]
+    pub committee: CommitteeArgs,
+    ///Secrets packed into each dealing
+    #[arg(long, default_value_t = 1)]
+    pub packing: u64,
+    #[command(flatten)]
+    pub family: FamilyArgs,
+}
+
+//The CLI and its scenarios report errors as plain text; the library itself only returns crate errors
+impl From<Error> for String {
+    fn from(error: Error) -> String {
+        error.to_string()
+    }
+}
+
+pub fn run(cli: Cli) -> Result<(), String> {
+    match &cli.command {
+        Command::Vss(args) => match args.protocol.field {
+            FieldChoice::Fq => run_vss::<Fq>(args, cli.output),
+            FieldChoice::Fr => run_vss::<Fr>(args, cli.output),
+        },
+        Command::Extract(args) => run_extract(args, cli.output),
+        Command::Beacon(args) => match args.protocol.field {
+            FieldChoice::Fq => run_beacon::<Fq>(args, cli.output),
+            FieldChoice::Fr => run_beacon::<Fr>(args, cli.output),
+        },
+        Command::Reshare(args) => match args.protocol.field {
+            FieldChoice::Fq => run_reshare::<Fq>(args, cli.output),
+            FieldChoice::Fr => run_reshare::<Fr>(args, cli.output),
+        },
+        Command::Dkg(args) => run_dkg(args, cli.output),
+        Command::Bench(args) => run_bench(args, cli.output),
+        Command::ThreadScaling(args) => run_thread_scaling(args),
+        Command::Scenarios(args) => run_scenarios(args, cli.output),
+        Command::Audit(args) => run_audit(args, cli.output),
+        Command::VerifyTranscript(args) => match args.field {
+            FieldChoice::Fq => run_verify_transcript::<Fq>(args, cli.output),
+            FieldChoice::Fr => run_verify_transcript::<Fr>(args, cli.output),
+        },
+        Command::Certify(args) => match args.transcript.field {
+            FieldChoice::Fq => run_certify::<Fq>(args, cli.output),
+            FieldChoice::Fr => run_certify::<Fr>(args, cli.output),
+        },
+    }
+}
+
+fn run_scenarios(args: &ScenarioArgs, output: Output) -> Result<(), String> {
+    let results = scenarios::run_all(args.t, args.seed);
+    match output {
+        Output::Human => {
+            for (name, result) in &results {
+                match result {
+                    Ok(()) => println!("{} | ok", name),
+                    Err(reason) => println!("{} | FAILED: {}", name, reason),
+                }
+            }
+        }
+        Output::Json => {
+            let rows: Vec<serde_json::Value> = results.iter()
+                .map(|(name, result)| json!({ "scenario": name, "passed": result.is_ok(), "failure": result.as_ref().err() }))
+                .collect();
+            println!("{}", serde_json::Value::Array(rows));
+        }
+    }
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if failed > 0 {
+        return Err(format!("{} of {} scenarios failed", failed, results.len()));
+    }
+    Ok(())
+}
+
+fn run_vss<F: Field>(args: &VssArgs, output: Output) -> Result<(), String> {
+    let pp = args.protocol.pub_params()?;
+    pp.validate()?;
+    if args.secret.len() as u64 != pp.packing {
+        return Err(format!("--secret takes one value per packed secret, {} are needed", pp.packing));
+    }
+    let vss: VSS<F> = VSS {
+        secrets: args.secret.iter().map(|secret| F::from(*secret)).collect(),
+        pp: pp,
+        execution_leaks: args.execution_leaks,
+        seed: args.protocol.committee.seed.unwrap_or(DEFAULT_DEALER_SEED),
+    };
+    let report = match &args.transcript {
+        Some(path) => {
+            let (report, transcript) = vss.execute_recorded(&mut Honest)?;
+            transcript.save(path)?;
+            report
+        },
+        None => vss.execute()?,
+    };
+    match output {
+        Output::Human => {
+            print_rejections([&report.costs]);
+            report.print();
+        },
+        Output::Json => println!("{}", vss_json(&report)),
+    }
+    if report.leakage.is_some() && !report.success {
+        return Err("secrecy or correctness did not survive the execution leaks".to_string());
+    }
+    Ok(())
+}
+
+//Messages and rows the roles rejected, on stderr so that they do not mix with the results
+fn print_rejections<'a>(reports: impl IntoIterator<Item = &'a CostReport>) {
+    for rejection in reports.into_iter().flat_map(|report| &report.rejections) {
+        eprintln!("{:?} {} rejected a message: {}", rejection.role, rejection.party, rejection.reason);
+    }
+}
+
+fn vss_json<F: Field>(report: &VssReport<F>) -> String {
+    let costs: serde_json::Value = serde_json::from_str(&report.costs.to_json()).unwrap();
+    let by_kind: serde_json::Value = serde_json::from_str(&report.costs.by_kind_to_json()).unwrap();
+    json!({
+        "secret_reconstructed": report.reconstructed,
+        "secrets": report.secrets.iter().map(|secret| secret.to_string()).collect::<Vec<String>>(),
+        "success": report.success,
+        "costs": costs,
+        "messages_by_kind": by_kind,
+        "rejections": report.costs.rejections,
+        "leakage": report.leakage,
+    }).to_string()
+}
+
+fn run_beacon<F: Field>(args: &BeaconArgs, output: Output) -> Result<(), String> {
+    let pp = args.protocol.pub_params()?;
+    pp.validate()?;
+    let beacon: RandExtractorVSSBased<F> = RandExtractorVSSBased::new(pp, args.execution_leaks, args.protocol.committee.seed);
+    let outcome = match &args.transcript {
+        Some(path) => {
+            let (outcome, transcript) = beacon.execute_recorded(&mut Honest)?;
+            transcript.save(path)?;
+            outcome
+        },
+        None => beacon.execute()?,
+    };
+    match output {
+        Output::Human => {
+            print_rejections(outcome.dealings.values());
+            outcome.print();
+        },
+        Output::Json => println!("{}", beacon_json(&outcome)),
+    }
+    if let Some(path) = &args.archive {
+        let mut board = FileBoard::open(path)?;
+        let round = board.last_round().map_or(0, |round| round + 1);
+        let mut value = Vec::new();
+        outcome.output.serialize_compressed(&mut value).map_err(Error::from)?;
+        board.store_round(round, &[BroadcastMessage::new(0, value)])?;
+        let checkpoint = board.checkpoint();
+        eprintln!("Archived as round {}; board height {}, head {}", round, checkpoint.height, checkpoint.head_hex());
+    }
+    if !outcome.success {
+        return Err("a qualified dealing could not be reconstructed".to_string());
+    }
+    Ok(())
+}
+
+fn beacon_json<F: Field>(outcome: &BeaconOutcome<F>) -> String {
+    let dealings: Vec<serde_json::Value> = outcome.dealings.iter()
+        .map(|(dealer, costs)| {
+            let total = costs.total();
+            json!({
+                "dealer": dealer,
+                "complaints": outcome.complaints.get(dealer),
+                "qualified": outcome.qualified.contains(dealer),
+                "messages": total.messages,
+                "bytes": total.bytes,
+                "time_ms": total.time.as_secs_f64() * 1000.0,
+            })
+        })
+        .collect();
+    json!({
+        "output": outcome.output.iter().map(|value| value.to_string()).collect::<Vec<String>>(),
+        "qualified": outcome.qualified,
+        "success": outcome.success,
+        "time_ms": outcome.time.as_secs_f64() * 1000.0,
+        "dealings": dealings,
+    }).to_string()
+}
+
+fn run_reshare<F: Field>(args: &ReshareArgs, output: Output) -> Result<(), String> {
+    let pp = args.protocol.pub_params()?;
+    pp.validate()?;
+    if args.secret.len() as u64 != pp.packing {
+        return Err(format!("--secret takes one value per packed secret, {} are needed", pp.packing));
+    }
+    let resharing: Resharing<F> = Resharing {
+        dealing: VSS {
+            secrets: args.secret.iter().map(|secret| F::from(*secret)).collect(),
+            pp: pp,
+            execution_leaks: false,
+            seed: args.protocol.committee.seed.unwrap_or(DEFAULT_DEALER_SEED),
+        },
+        epochs: args.epochs,
+    };
+    let outcome = resharing.execute_against(&mut Honest)?;
+    match output {
+        Output::Human => {
+            let epochs = outcome.handovers.iter().flat_map(|handover| handover.dealings.values());
+            print_rejections(std::iter::once(&outcome.dealing).chain(epochs).chain([&outcome.opening]));
+            outcome.print();
+        },
+        Output::Json => println!("{}", reshare_json(&outcome)),
+    }
+    if !outcome.success {
+        return Err("the last committee did not open the secrets that were dealt".to_string());
+    }
+    Ok(())
+}
+
+fn reshare_json<F: Field>(outcome: &ResharingOutcome<F>) -> String {
+    let epochs: Vec<serde_json::Value> = outcome.handovers.iter()
+        .map(|handover| {
+            let total = handover.total();
+            json!({
+                "qualified": handover.qualified,
+                "complaints": handover.complaints,
+                "misdealt": handover.misdealt,
+                "holders": handover.holding.shares.keys().collect::<Vec<&u64>>(),
+                "messages": total.messages,
+                "bytes": total.bytes,
+                "time_ms": total.time.as_secs_f64() * 1000.0,
+            })
+        })
+        .collect();
+    let dealing = outcome.dealing.total();
+    json!({
+        "secrets": outcome.secrets.iter().map(|secret| secret.to_string()).collect::<Vec<String>>(),
+        "secrets_reconstructed": outcome.reconstructed,
+        "success": outcome.success,
+        "dealing": { "messages": dealing.messages, "bytes": dealing.bytes, "time_ms": dealing.time.as_secs_f64() * 1000.0 },
+        "epochs": epochs,
+        "time_ms": outcome.time.as_secs_f64() * 1000.0,
+    }).to_string()
+}
+
+fn run_dkg(args: &DkgArgs, output: Output) -> Result<(), String> {
+    let pp = args.committee.pub_params(1)?;
+    pp.validate()?;
+    let dkg = Dkg { pp: pp, seed: args.committee.seed };
+    let outcome = dkg.execute()?;
+    match output {
+        Output::Human => {
+            print_rejections(outcome.dealings.values());
+            outcome.print()?;
+        },
+        Output::Json => println!("{}", dkg_json(&outcome)?),
+    }
+    if !outcome.success {
+        return Err("the key shares do not match the public key".to_string());
+    }
+    Ok(())
+}
+
+fn dkg_json(outcome: &DkgOutcome) -> Result<String, String> {
+    let dealings: Vec<serde_json::Value> = outcome.dealings.iter()
+        .map(|(dealer, costs)| {
+            let total = costs.total();
+            json!({
+                "dealer": dealer,
+                "complaints": outcome.complaints.get(dealer),
+                "qualified": outcome.qualified.contains(dealer),
+                "messages": total.messages,
+                "bytes": total.bytes,
+                "time_ms": total.time.as_secs_f64() * 1000.0,
+            })
+        })
+        .collect();
+    let mut verification_keys = serde_json::Map::new();
+    for (receiver, key) in &outcome.verification_keys {
+        let mut bytes = Vec::new();
+        key.serialize_compressed(&mut bytes).map_err(Error::from)?;
+        verification_keys.insert(receiver.to_string(), json!(to_hex(&bytes)));
+    }
+    Ok(json!({
+        "public_key": to_hex(&outcome.public_key_bytes()?),
+        "verification_keys": verification_keys,
+        "holders": outcome.shares.keys().collect::<Vec<&u64>>(),
+        "qualified": outcome.qualified,
+        "success": outcome.success,
+        "time_ms": outcome.time.as_secs_f64() * 1000.0,
+        "dealings": dealings,
+    }).to_string())
+}
+
+fn run_audit(args: &AuditArgs, output: Output) -> Result<(), String> {
+    let from = match (args.height, &args.head) {
+        (Some(height), Some(head)) => Checkpoint::from_hex(height, head)?,
+        _ => Checkpoint::genesis(),
+    };
+    let head = FileBoard::verify_from(&args.board, &from)?;
+    match output {
+        Output::Human => println!("Board verifies from height {} to height {}, head {}", from.height, head.height, head.head_hex()),
+        Output::Json => println!("{}", json!({ "from": from.height, "height": head.height, "head": head.head_hex() })),
+    }
+    Ok(())
+}
+
+fn run_verify_transcript<F: Field>(args: &VerifyTranscriptArgs, output: Output) -> Result<(), String> {
+    let transcript = Transcript::load(&args.transcript)?;
+    match &transcript.protocol {
+        Protocol::Vss { .. } => report_verification(&VSS::<F>::verify_transcript(&transcript)?.map(|values| display_values(&values)), output),
+        Protocol::Beacon { .. } => report_verification(&RandExtractorVSSBased::<F>::verify_transcript(&transcript)?.map(|values| display_values(&values)), output),
+        Protocol::Extractor { .. } => report_verification(&RandomnessExtractor::verify_transcript(&transcript)?, output),
+    }
+}
+
+//Deviations are reported, but only an output that differs from the claimed one fails the verification
+fn report_verification<O: PartialEq + std::fmt::Display>(verification: &Verification<O>, output: Output) -> Result<(), String> {
+    match output {
+        Output::Human => {
+            for deviation in &verification.deviations {
+                println!("session {} | round {} | party {} | {}", deviation.session, deviation.round, deviation.party, deviation.reason);
+            }
+            println!("{} deviations", verification.deviations.len());
+            println!("Recomputed output: {}", verification.output);
+            println!("Claimed output: {}", verification.claimed);
+        }
+        Output::Json => println!("{}", json!({
+            "output": verification.output.to_string(),
+            "claimed": verification.claimed.to_string(),
+            "matches": verification.output_matches(),
+            "deviations": verification.deviations,
+        })),
+    }
+    if !verification.output_matches() {
+        return Err("the recomputed output differs from the claimed one".to_string());
+    }
+    Ok(())
+}
+
+fn run_certify<F: Field>(args: &CertifyArgs, output: Output) -> Result<(), String> {
+    let transcript = Transcript::load(&args.transcript.transcript)?;
+    let verdict: Verdict = match transcript.protocol {
+        Protocol::Vss { t, n, packing } => {
+            let publications = publications_of::<F>(&transcript, 0, t, n);
+            Auditor::new(t, n).with_packing(packing).audit(&transcript.keys_of(0)?, &publications, &claimed_output::<F>(&transcript)?)?
+        },
+        Protocol::Beacon { t, n, packing } => {
+            //Only qualified dealings were reconstructed, so they are the ones with publications
+            let mut dealings = Vec::new();
+            for dealer in 1..=n {
+                let publications = publications_of::<F>(&transcript, dealer, t, n);
+                if !publications.is_empty() {
+                    dealings.push((transcript.keys_of(dealer)?, publications));
+                }
+            }
+            Auditor::new(t, n).with_packing(packing).audit_beacon(&dealings, &claimed_output::<F>(&transcript)?)?
+        },
+        Protocol::Extractor { .. } => return Err("only VSS and beacon outputs can be certified".to_string()),
+    };
+    let certificate = serde_json::to_string(&verdict.certificate).map_err(|error| error.to_string())?;
+    if let Some(path) = &args.certificate {
+        std::fs::write(path, &certificate).map_err(|error| format!("cannot write {}: {}", path.display(), error))?;
+    }
+    match output {
+        Output::Human => {
+            for reason in &verdict.reasons {
+                println!("{}", reason);
+            }
+            println!("Output {}: {}", verdict.certificate.output.join(", "), if verdict.passed { "passed" } else { "FAILED" });
+        }
+        Output::Json => println!("{}", json!({
+            "passed": verdict.passed,
+            "reasons": verdict.reasons,
+            "certificate": verdict.certificate,
+        })),
+    }
+    if !verdict.passed {
+        return Err("the claimed output did not pass the audit".to_string());
+    }
+    Ok(())
+}
+
+fn run_extract(args: &ExtractArgs, output: Output) -> Result<(), String> {
+    let params = args.family.extractor_params(args.t, args.n, args.seed)?;
+    let rand_extr = RandomnessExtractor { params: params, seed: args.seed };
+    let outcome = match &args.transcript {
+        Some(path) => {
+            let (outcome, transcript) = rand_extr.execute_recorded(&mut Honest)?;
+            transcript.save(path)?;
+            outcome
+        },
+        None => rand_extr.execute()?,
+    };
+    match output {
+        Output::Human => {
+            println!("{}", rand_extr.params.describe());
+            outcome.print();
+        }
+        Output::Json => {
+            let report: serde_json::Value = serde_json::from_str(&outcome.report.to_json()).unwrap();
+            let by_kind: serde_json::Value = serde_json::from_str(&outcome.report.by_kind_to_json()).unwrap();
+            println!("{}", json!({
+                "coin": outcome.coin,
+                "time_ms": outcome.time.as_secs_f64() * 1000.0,
+                "costs": report,
+                "messages_by_kind": by_kind,
+            }));
+        }
+    }
+    if let Some(path) = &args.costs_csv {
+        std::fs::write(path, outcome.report.to_csv()).map_err(|error| format!("cannot write {}: {}", path.display(), error))?;
+    }
+    Ok(())
+}
+
+fn run_bench(args: &BenchArgs, output: Output) -> Result<(), String> {
+    if args.repetitions == 0 || args.t_step == 0 || args.t_from == 0 || args.t_from > args.t_to {
+        return Err("the sweep needs 1 <= t-from <= t-to, a positive t-step and at least one repetition".to_string());
+    }
+    let ts = (args.t_from..=args.t_to).step_by(args.t_step as usize);
+    if args.packing == 0 {
+        return Err("a dealing shares at least one secret".to_string());
+    }
+    let authentication = authentication(args.signature_scheme);
+    let rows = match args.field {
+        FieldChoice::Fq => sweep_vss::<Fq>(ts, args.repetitions, args.seed, authentication, args.packing)?,
+        FieldChoice::Fr => sweep_vss::<Fr>(ts, args.repetitions, args.seed, authentication, args.packing)?,
+    };
+    let csv = sweep_csv(&rows);
+
+    match &args.csv {
+        Some(path) => std::fs::write(path, csv).map_err(|error| format!("cannot write {}: {}", path.display(), error))?,
+        None => print!("{}", csv),
+    }
+    if let (Some(_), Output::Human) = (&args.csv, output) {
+        for row in &rows {
+            println!("t = {}: overall time {:.1} +- {:.1} ms, overall comm {:.3} +- {:.3} MB, per secret {:.1} ms and {:.3} MB",
+                        row.t, row.mean(Metric::OverallTime), row.stddev(Metric::OverallTime), row.mean(Metric::OverallComm), row.stddev(Metric::OverallComm),
+                        row.mean(Metric::OverallTimePerSecret), row.mean(Metric::OverallCommPerSecret));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+fn run_thread_scaling(args: &ThreadScalingArgs) -> Result<(), String> {
+    let pp = args.committee.pub_params(args.packing)?;
+    pp.validate()?;
+    let params = args.family.extractor_params(args.committee.t, args.committee.n, args.committee.seed)?;
+    crate::bench::thread_scaling(&pp, &params, args.committee.seed.unwrap_or(DEFAULT_DEALER_SEED))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parallel"))]
+fn run_thread_scaling(_args: &ThreadScalingArgs) -> Result<(), String> {
+    Err("thread scaling benchmarks need the `parallel` feature".to_string())
+}
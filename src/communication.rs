@@ -1,40 +1,163 @@
 use std::collections::BTreeMap;
 
-#[derive(Default)]
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::Rng;
+use sha2::Sha256;
+use x25519_dalek::{SharedSecret, StaticSecret};
+pub use x25519_dalek::PublicKey as EncryptionKey;
+
+use crate::error::{Error, Result};
+
+///Board the protocols post their broadcast rounds on
+pub trait BulletinBoard {
+    ///Posts messages in a round, after whatever was posted in it before
+    fn store_round(&mut self, round: u64, messages: &[BroadcastMessage]) -> Result<()>;
+
+    fn read_round(&self, round: u64) -> Option<&Vec<BroadcastMessage>>;
+}
+
+///Ideal bulletin board: everybody reads exactly what was posted
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct BroadcastChannel {
     ///Stores all messages that were broadcast as a tuple (round sent, messages from that round) 
     messages: BTreeMap<u64, Vec<BroadcastMessage>>
 }
 
-impl BroadcastChannel {
-    pub fn store_round(&self, round: u64, messages: &Vec<BroadcastMessage>) {
-        //self.messages.insert(round, messages.to_vec());
+impl BulletinBoard for BroadcastChannel {
+    fn store_round(&mut self, round: u64, messages: &[BroadcastMessage]) -> Result<()> {
+        self.messages.entry(round).or_default().extend(messages.iter().cloned());
+        Ok(())
     }
 
-    pub fn read_round(&self, round: u64) -> Option<&Vec<BroadcastMessage>> {
+    fn read_round(&self, round: u64) -> Option<&Vec<BroadcastMessage>> {
         self.messages.get(&round)
     }
 }
 
+impl BroadcastChannel {
+    ///Rounds with at least one post, in increasing order
+    pub fn rounds(&self) -> Vec<u64> {
+        self.messages.keys().copied().collect()
+    }
+}
+
+///Key a role decrypts its private messages with. The public half is published before anyone sends to the role,
+///so a private channel to a role that has not spoken yet is realized by encrypting to it.
+pub struct RoleKey {
+    secret: StaticSecret,
+    public: EncryptionKey
+}
+
+impl RoleKey {
+    pub fn generate<R: Rng>(rng: &mut R) -> RoleKey {
+        RoleKey::from_bytes(rng.gen())
+    }
+
+    pub fn public(&self) -> EncryptionKey {
+        self.public
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.secret.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> RoleKey {
+        let secret = StaticSecret::from(bytes);
+        let public = EncryptionKey::from(&secret);
+        RoleKey { secret: secret, public: public }
+    }
+
+    pub fn decrypt(&self, ciphertext: &Ciphertext) -> Result<Vec<u8>> {
+        let ephemeral = EncryptionKey::from(ciphertext.ephemeral);
+        let cipher = payload_cipher(&self.secret.diffie_hellman(&ephemeral), &ephemeral, &self.public);
+        cipher.decrypt(Nonce::from_slice(&NONCE), ciphertext.payload.as_slice()).map_err(|_| Error::Decryption)
+    }
+}
+
+///Payload encrypted to one role key: the sender's ephemeral X25519 key and the ChaCha20-Poly1305 ciphertext
+#[derive(Clone)]
+pub struct Ciphertext {
+    ephemeral: [u8; 32],
+    payload: Vec<u8>
+}
+
+impl Ciphertext {
+    pub fn serialized_size(&self) -> u64 {
+        (self.ephemeral.len() + self.payload.len()) as u64
+    }
+}
+
+//Every payload key is used once, so a fixed nonce is safe
+const NONCE: [u8; 12] = [0; 12];
+
+//Payload key derived from the shared secret and both public keys
+fn payload_cipher(shared: &SharedSecret, ephemeral: &EncryptionKey, recipient: &EncryptionKey) -> ChaCha20Poly1305 {
+    let mut info = ephemeral.as_bytes().to_vec();
+    info.extend_from_slice(recipient.as_bytes());
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, shared.as_bytes()).expand(&info, &mut key).expect("32 bytes is a valid HKDF-SHA256 output length");
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+///Hybrid encryption to `recipient`: X25519 with a fresh ephemeral key, HKDF-SHA256 and ChaCha20-Poly1305
+pub fn encrypt(recipient: &EncryptionKey, plaintext: &[u8]) -> Result<Ciphertext> {
+    //Drawn for this ciphertext only and dropped with it
+    let ephemeral_secret = StaticSecret::from(OsRng.gen::<[u8; 32]>());
+    let ephemeral = EncryptionKey::from(&ephemeral_secret);
+    let cipher = payload_cipher(&ephemeral_secret.diffie_hellman(recipient), &ephemeral, recipient);
+    let payload = cipher.encrypt(Nonce::from_slice(&NONCE), plaintext).map_err(|_| Error::Encryption)?;
+    Ok(Ciphertext { ephemeral: ephemeral.to_bytes(), payload: payload })
+}
+
+///Channel to a single role; whatever is stored is encrypted to the role's key and only that key reads it back
 #[derive(Clone)]
 pub struct PrivateChannel {
-    ///Stores the message that were sent in this private channel (round sent, payload>
-    message: Option<Vec<u8>>
+    recipient: EncryptionKey,
+    ///The message that was sent in this private channel, encrypted
+    message: Option<Ciphertext>
 }
 
 impl PrivateChannel {
-    pub fn store_msg(&self, message: &Vec<u8>) {
-       // self.message = Some(message.to_vec());
+    pub fn new(recipient: EncryptionKey) -> PrivateChannel {
+        PrivateChannel { recipient: recipient, message: None }
     }
 
-    pub fn read_msg_from_round(&self) -> &Option<Vec<u8>> {
-        &self.message
+    pub fn store_msg(&mut self, message: &[u8]) -> Result<()> {
+        self.message = Some(encrypt(&self.recipient, message)?);
+        Ok(())
+    }
+
+    ///The decrypted message, or None if nothing was sent
+    pub fn read_msg_from_round(&self, key: &RoleKey) -> Result<Option<Vec<u8>>> {
+        self.message.as_ref().map(|ciphertext| key.decrypt(ciphertext)).transpose()
+    }
+
+    ///Bytes on the wire, zero if nothing was sent
+    pub fn serialized_size(&self) -> u64 {
+        self.message.as_ref().map_or(0, |ciphertext| ciphertext.serialized_size())
     }
 }
 
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct BroadcastMessage {
     sender: u64,
     payload: Vec<u8>
+}
+
+impl BroadcastMessage {
+    pub fn new(sender: u64, payload: Vec<u8>) -> BroadcastMessage {
+        BroadcastMessage { sender: sender, payload: payload }
+    }
+
+    pub fn sender(&self) -> u64 {
+        self.sender
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
 }
\ No newline at end of file
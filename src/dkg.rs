@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use ark_bls12_381::{Fr, G1Affine, G1Projective};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_serialize::CanonicalSerialize;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::adversary::{Adversary, DealingView, Honest};
+use crate::board::to_hex;
+use crate::error::{Error, Result};
+use crate::metrics::{CostReport, MessageKind, Metrics, Role, RoleCost};
+use crate::reshare::{lagrange_weights, Holding};
+use crate::vss::{points_of, Dealer, PubParams, Resolver, VSS};
+
+//Distributed key generation from parallel dealings. Every receiver of a committee deals a random secret to the next
+//committee and publishes a Feldman commitment to the column of its polynomial at 0: each coefficient times the
+//generator of G1. A receiver whose row at 0 is not what the commitment gives complains as it would about a bad row,
+//so a dealing qualifies by the beacon's rule and the commitment of a qualified dealing is to the column the honest
+//receivers hold. The secret key is the sum of the qualified dealers' secrets and each receiver's key share the sum of
+//its values; the public key and the verification keys of the shares follow from the commitments alone.
+//As in any joint-Feldman DKG, a rushing dealer sees the honest commitments before its own dealing is judged and can
+//bias the public key by getting itself disqualified.
+
+///Key generation by the receivers of `pp`, who deal to a fresh committee of the same size nominated among the same parties
+pub struct Dkg {
+    pub pp: PubParams,
+    //Seed for the dealers' secrets and polynomials, fresh randomness if None
+    pub seed: Option<u64>,
+}
+
+///Threshold key held by the new committee and what is public about it
+pub struct DkgOutcome {
+    pub public_key: G1Affine,
+    //Public keys of the key shares, by receiver of the new committee, to check partial signatures against
+    pub verification_keys: BTreeMap<u64, G1Affine>,
+    //Key shares of the receivers that kept a row from every qualified dealing; each is known only to its receiver
+    pub shares: BTreeMap<u64, Fr>,
+    //Dealers whose secret is part of the key
+    pub qualified: Vec<u64>,
+    pub complaints: BTreeMap<u64, u64>,
+    //Costs of each dealing, indexed by dealer
+    pub dealings: BTreeMap<u64, CostReport>,
+    //Whether every share matches its verification key and t+1 shares open to the key of the public key
+    pub success: bool,
+    pub time: Duration,
+}
+
+//Feldman commitment to the column at 0 of the dealer's polynomial
+fn commit(dealer: &Dealer<Fr>) -> Result<Vec<G1Affine>> {
+    let column = dealer.polynomial()?.row(Fr::ZERO);
+    let generator = G1Projective::generator();
+    let commitment: Vec<G1Projective> = (0..=dealer.pp.degree())
+        .map(|k| generator * column.coeffs.get(k as usize).copied().unwrap_or(Fr::ZERO))
+        .collect();
+    Ok(G1Projective::normalize_batch(&commitment))
+}
+
+//The committed column at x, in the exponent
+fn evaluate_commitment(commitment: &[G1Affine], x: Fr) -> G1Projective {
+    commitment.iter().rev().fold(G1Projective::zero(), |value, coefficient| value * x + coefficient)
+}
+
+impl Dkg {
+    pub fn execute(&self) -> Result<DkgOutcome> {
+        self.execute_against(&mut Honest)
+    }
+
+    ///Runs the DKG with the parties `byzantine` corrupts; party i is dealer i as well as receiver i of the new committee
+    pub fn execute_against<A: Adversary>(&self, byzantine: &mut A) -> Result<DkgOutcome> {
+        let t = self.pp.t;
+        let n = self.pp.n;
+        if self.pp.packing != 1 {
+            return Err(Error::InvalidParams("a key is a single secret, the DKG does not pack".to_string()));
+        }
+        let start_time = SystemTime::now();
+        let mut rng: StdRng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::seed_from_u64(rand::random()),
+        };
+        let next = self.pp.next_committee()?;
+        let (points, _) = points_of::<Fr>(&next.roles.signing_keys()[1..], 1)?;
+
+        //Every dealer publishes its commitment and deals; every receiver checks its row against the commitment
+        let mut commitments: BTreeMap<u64, Vec<G1Affine>> = BTreeMap::new();
+        let mut holdings: BTreeMap<u64, Holding<Fr>> = BTreeMap::new();
+        let mut complaints: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut costs_per_dealing: BTreeMap<u64, CostReport> = BTreeMap::new();
+        for dealer in 1..=n {
+            let vss: VSS<Fr> = VSS {
+                secrets: vec![Fr::rand(&mut rng)],
+                pp: self.pp.dealing_to(dealer, &next)?,
+                execution_leaks: false,
+                seed: rng.gen()
+            };
+            let mut costs = CostReport::default();
+
+            let commit_time = SystemTime::now();
+            let mut commitment = commit(&Dealer::new(vss.secrets.clone(), &vss.pp, vss.seed)?)?;
+            if byzantine.is_corrupted(dealer) {
+                byzantine.tamper_commitment(dealer, &mut commitment);
+            }
+            costs.record(vss.pp.dealer_id(), Role::Dealer, 0, 0, SystemTime::now().duration_since(commit_time).unwrap());
+            costs.record_messages(vss.pp.dealer_id(), Role::Dealer, MessageKind::DealerCommitment, 1, commitment.compressed_size() as u64);
+
+            let sharing = vss.share_phase(&mut costs, None, &mut DealingView { adversary: byzantine, dealer: dealer }, None)?;
+            let mut holding = Holding::of(&sharing, &vss.pp)?;
+            let mut dealing_complaints = sharing.complaints;
+            for i in 1..=n {
+                let check_time = SystemTime::now();
+                //A receiver that already complained about its row holds nothing to check
+                let share = match holding.shares.get(&i) {
+                    Some(values) => values[0],
+                    None => continue,
+                };
+                if commitment.len() as u64 != t + 1 || evaluate_commitment(&commitment, points[(i - 1) as usize]) != G1Projective::generator() * share {
+                    costs.record_rejection(i, Role::Receiver, &Error::CommitmentMismatch { dealer: dealer });
+                    holding.shares.remove(&i);
+                    dealing_complaints += 1;
+                }
+                costs.record(i, Role::Receiver, 0, 0, SystemTime::now().duration_since(check_time).unwrap());
+            }
+
+            commitments.insert(dealer, commitment);
+            holdings.insert(dealer, holding);
+            complaints.insert(dealer, dealing_complaints);
+            costs_per_dealing.insert(dealer, costs);
+        }
+
+        //At most t dealers are corrupted, so with t+1 qualified dealings an honest secret is part of the key
+        let qualified = Resolver { t: t }.qualify(&complaints);
+        if (qualified.len() as u64) < t + 1 {
+            return Err(Error::NotEnoughShares { needed: t + 1, verified: qualified.len() as u64 });
+        }
+
+        let public_key: G1Projective = qualified.iter().map(|dealer| G1Projective::from(commitments[dealer][0])).sum();
+        let verification_keys: Vec<G1Projective> = (1..=n)
+            .map(|i| qualified.iter().map(|dealer| evaluate_commitment(&commitments[dealer], points[(i - 1) as usize])).sum())
+            .collect();
+        let verification_keys: BTreeMap<u64, G1Affine> = (1..=n).zip(G1Projective::normalize_batch(&verification_keys)).collect();
+        let shares: BTreeMap<u64, Fr> = (1..=n)
+            .filter_map(|i| {
+                qualified.iter()
+                    .map(|dealer| holdings[dealer].shares.get(&i).map(|values| values[0]))
+                    .sum::<Option<Fr>>()
+                    .map(|share| (i, share))
+            })
+            .collect();
+
+        //Simulation check: the shares are those of the key behind the public key
+        let opening: Vec<(u64, Fr)> = shares.iter().take((t + 1) as usize).map(|(i, share)| (*i, *share)).collect();
+        let opening_points: Vec<Fr> = opening.iter().map(|(i, _)| points[(i - 1) as usize]).collect();
+        let secret_key: Fr = lagrange_weights(&opening_points, Fr::ZERO).into_iter().zip(&opening).map(|(weight, (_, share))| weight * share).sum();
+        let success = opening.len() as u64 == t + 1
+            && G1Projective::generator() * secret_key == public_key
+            && shares.iter().all(|(i, share)| G1Projective::generator() * share == verification_keys[i]);
+
+        Ok(DkgOutcome {
+            public_key: public_key.into_affine(),
+            verification_keys: verification_keys,
+            shares: shares,
+            qualified: qualified,
+            complaints: complaints,
+            dealings: costs_per_dealing,
+            success: success,
+            time: SystemTime::now().duration_since(start_time).unwrap()
+        })
+    }
+}
+
+impl DkgOutcome {
+    pub fn total(&self) -> RoleCost {
+        let mut total = RoleCost::default();
+        for costs in self.dealings.values() {
+            let dealing = costs.total();
+            total.messages += dealing.messages;
+            total.bytes += dealing.bytes;
+            total.time += dealing.time;
+        }
+        total
+    }
+
+    ///Compressed public key
+    pub fn public_key_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.public_key.serialize_compressed(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn print(&self) -> Result<()> {
+        println!("dealer | complaints | qualified | time ms | comm MB");
+        for (dealer, costs) in &self.dealings {
+            let total = costs.total();
+            println!("{} | {} | {} | {} | {}", dealer, self.complaints.get(dealer).copied().unwrap_or_default(), self.qualified.contains(dealer), total.time.as_millis(), (total.bytes as f64)/1000000.0);
+        }
+        let total = self.total();
+        println!("Whole protocol takes {} milliseconds", self.time.as_millis());
+        println!("Overall time: {}", total.time.as_millis());
+        println!("Overall comm: {}", (total.bytes as f64)/1000000.0);
+        println!("Key shares held by receivers {:?}", self.shares.keys().collect::<Vec<&u64>>());
+        println!("Public key: {}, success: {}", to_hex(&self.public_key_bytes()?), self.success);
+        Ok(())
+    }
+}
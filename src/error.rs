@@ -0,0 +1,86 @@
+use std::fmt;
+
+use ark_serialize::SerializationError;
+
+use crate::polynomials::InterpolationError;
+
+///Everything that can go wrong in the crate.
+///Checks on messages from other parties fail with these errors too; the receiving role
+///turns them into a complaint or an accusation against the sender and carries on.
+#[derive(Debug)]
+pub enum Error {
+    ///Parameters or a corruption the protocols cannot run with
+    InvalidParams(String),
+    ///The signature of the party with pki index `signer` is missing
+    MissingSignature { signer: u64 },
+    ///The signature of the party with pki index `signer` does not verify
+    BadSignature { signer: u64 },
+    ///Both signatures verify but `from` holds a different value than the dealer gave us
+    InconsistentValue { from: u64 },
+    ///`from` already sent its message for this round
+    DuplicateMessage { from: u64 },
+    ///`from` sent something that cannot be a protocol message
+    MalformedMessage { from: u64, reason: String },
+    ///A row does not match the commitment of `dealer`
+    CommitmentMismatch { dealer: u64 },
+    ///Fewer verified subshares or rows than needed
+    NotEnoughShares { needed: u64, verified: u64 },
+    ///The values do not lie on a polynomial of degree at most `max`
+    DegreeTooHigh { degree: u64, max: u64 },
+    ///A private message could not be encrypted to its recipient
+    Encryption,
+    ///A private message does not decrypt under the recipient's role key
+    Decryption,
+    ///Record `record` of a bulletin board does not parse or does not extend the hash chain
+    BrokenBoard { record: u64, reason: String },
+    ///A transcript that cannot be read or replayed
+    Transcript(String),
+    Interpolation(InterpolationError),
+    Serialization(SerializationError),
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidParams(reason) => write!(f, "{}", reason),
+            Error::MissingSignature { signer } => write!(f, "signature of party {} is missing", signer),
+            Error::BadSignature { signer } => write!(f, "signature of party {} does not verify", signer),
+            Error::InconsistentValue { from } => write!(f, "party {} holds a different value than the dealer sent", from),
+            Error::DuplicateMessage { from } => write!(f, "party {} already spoke", from),
+            Error::MalformedMessage { from, reason } => write!(f, "malformed message from party {}: {}", from, reason),
+            Error::CommitmentMismatch { dealer } => write!(f, "row does not match the commitment of dealer {}", dealer),
+            Error::NotEnoughShares { needed, verified } => write!(f, "{} verified shares, {} are needed", verified, needed),
+            Error::DegreeTooHigh { degree, max } => write!(f, "degree {} exceeds {}", degree, max),
+            Error::Encryption => write!(f, "encryption failed"),
+            Error::Decryption => write!(f, "decryption failed"),
+            Error::BrokenBoard { record, reason } => write!(f, "record {} of the bulletin board: {}", record, reason),
+            Error::Transcript(reason) => write!(f, "unusable transcript: {}", reason),
+            Error::Interpolation(error) => write!(f, "interpolation failed: {}", error),
+            Error::Serialization(error) => write!(f, "serialization failed: {}", error),
+            Error::Io(error) => write!(f, "I/O failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<InterpolationError> for Error {
+    fn from(error: InterpolationError) -> Error {
+        Error::Interpolation(error)
+    }
+}
+
+impl From<SerializationError> for Error {
+    fn from(error: SerializationError) -> Error {
+        Error::Serialization(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::Io(error)
+    }
+}
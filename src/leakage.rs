@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ark_ff::Field;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ed25519_dalek::*;
+use serde::Serialize;
+
+use crate::polynomials::rank;
+use crate::vss::{signed_bytes, Endorsement, Subshare};
+
+//YOSO with execution leaks: once a role has spoken its state is handed to the adversary.
+//Every role leaks its signing key; the rows received by t receivers leak as well.
+//The dealer's polynomial is its input and is not part of the leak.
+//A leaked key lets the adversary sign as that role, and it gets its forgeries in before the role's own messages.
+
+///Adversary collecting the state of roles after they speak and replaying it as forgeries
+pub struct LeakageAdversary<F: Field> {
+    //Receivers whose received row leaks, at most t of them
+    pub leaked_receivers: BTreeSet<u64>,
+    //Signing keys by pki index: 0 is the dealer, i is receiver i
+    keypairs: BTreeMap<u64, Keypair>,
+    rows: BTreeMap<u64, BTreeMap<u64, Subshare<F>>>,
+    forgeries_attempted: u64,
+    forgeries_accepted: u64,
+}
+
+///What the adversary learned and achieved in one run
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LeakageReport {
+    pub leaked_keys: usize,
+    pub leaked_rows: usize,
+    pub forgeries_attempted: u64,
+    pub forgeries_accepted: u64,
+    ///The leaked rows are consistent with every choice of the secrets, so they say nothing about them
+    pub secrecy_holds: bool,
+}
+
+fn copy_keypair(keypair: &Keypair) -> Keypair {
+    Keypair::from_bytes(&keypair.to_bytes()).unwrap()
+}
+
+impl<F: Field> LeakageAdversary<F> {
+    ///Picks t of the n receivers whose rows will leak
+    pub fn new(t: u64, n: u64, seed: u64) -> LeakageAdversary<F> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut receivers: Vec<u64> = (1..=n).collect();
+        for i in 0..t.min(n) as usize {
+            let j = rng.gen_range(i..n as usize);
+            receivers.swap(i, j);
+        }
+        LeakageAdversary {
+            leaked_receivers: receivers.into_iter().take(t as usize).collect(),
+            keypairs: BTreeMap::new(),
+            rows: BTreeMap::new(),
+            forgeries_attempted: 0,
+            forgeries_accepted: 0,
+        }
+    }
+
+    ///Hands the state of the role with pki index `id` to the adversary after it has spoken
+    pub fn leak(&mut self, id: u64, keypair: &Keypair, row: Option<&BTreeMap<u64, Subshare<F>>>) {
+        self.keypairs.insert(id, copy_keypair(keypair));
+        if let Some(row) = row {
+            if self.leaked_receivers.contains(&id) {
+                self.rows.insert(id, row.clone());
+            }
+        }
+    }
+
+    ///Signs `value` as the subshare at `position` by the dealer followed by `signers`, or None if one of the keys has not leaked yet
+    pub fn forge(&mut self, value: F, position: (u64, u64), signers: &[u64]) -> Option<Subshare<F>> {
+        let value_bytes = signed_bytes(&value, &self.keypairs.get(&0)?.public, position.0, position.1).ok()?;
+        let mut signatures: Vec<Signature> = Vec::new();
+        for id in std::iter::once(&0).chain(signers) {
+            signatures.push(self.keypairs.get(id)?.sign(&value_bytes));
+        }
+        self.forgeries_attempted += 1;
+        Some(Subshare {
+            value: value,
+            dealer_signature: signatures.first().cloned().map(Endorsement::Signature),
+            p_i_signature: signatures.get(1).cloned().map(Endorsement::Signature),
+            p_j_signature: signatures.get(2).cloned().map(Endorsement::Signature),
+        })
+    }
+
+    ///Records that a forged message is still part of what an honest role goes on with
+    pub fn forgery_accepted(&mut self) {
+        self.forgeries_accepted += 1;
+    }
+
+    ///Sums up the run of a sharing of `degree` with the receivers' `points` and the `secret_points` of its slots
+    pub fn report(&self, points: &[F], secret_points: &[F], degree: u64) -> LeakageReport {
+        LeakageReport {
+            leaked_keys: self.keypairs.len(),
+            leaked_rows: self.rows.len(),
+            forgeries_attempted: self.forgeries_attempted,
+            forgeries_accepted: self.forgeries_accepted,
+            secrecy_holds: self.secrets_hidden(points, secret_points, degree),
+        }
+    }
+
+    //A leaked entry (i, k) tells the adversary one linear function of the coefficients of the dealer's polynomial, a secret is another one.
+    //If the secrets stay independent of everything leaked, some symmetric polynomial of the same degree vanishes on every leaked entry
+    //and takes any given values at the secret points; adding it to the dealer's polynomial changes the secrets but not the view.
+    fn secrets_hidden(&self, points: &[F], secret_points: &[F], degree: u64) -> bool {
+        let terms: Vec<(u64, u64)> = (0..=degree).flat_map(|a| (a..=degree).map(move |b| (a, b))).collect();
+        let coefficients_of = |x: F, y: F| -> Vec<F> {
+            terms.iter().map(|(a, b)| match a == b {
+                true => x.pow([*a]) * y.pow([*b]),
+                false => x.pow([*a]) * y.pow([*b]) + x.pow([*b]) * y.pow([*a]),
+            }).collect()
+        };
+        let view: Vec<Vec<F>> = self.rows.iter()
+                                    .flat_map(|(i, row)| row.keys().map(move |k| (*i, *k)))
+                                    .filter_map(|(i, k)| Some(coefficients_of(*points.get(i as usize - 1)?, *points.get(k as usize - 1)?)))
+                                    .collect();
+        let secrets: Vec<Vec<F>> = secret_points.iter().map(|point| coefficients_of(*point, *point)).collect();
+        let view_rank = rank(view.clone());
+        rank(view.into_iter().chain(secrets).collect()) == view_rank + secret_points.len()
+    }
+}
+
+impl LeakageReport {
+    pub fn print(&self) {
+        println!("Leaked keys: {}, leaked rows: {}", self.leaked_keys, self.leaked_rows);
+        println!("Forgeries attempted: {}, accepted: {}", self.forgeries_attempted, self.forgeries_accepted);
+        println!("Secrecy holds: {}", self.secrecy_holds);
+    }
+}
@@ -1,24 +1,49 @@
-#![allow(unused_imports)]
-#![allow(warnings, unused)]
+//Struct literals spell out `field: field`, the protocol types keep their paper names (VSS),
+//and protocol steps take their parameters and tuples as the paper lays them out.
+#![allow(clippy::redundant_field_names, clippy::upper_case_acronyms, clippy::too_many_arguments, clippy::type_complexity)]
+
+//Turns a sequential iterator into a parallel one when the `parallel` feature is on.
+//Used for the lazy subset enumerations, which rayon cannot split on its own.
+macro_rules! cfg_par_bridge {
+    ($e: expr) => {{
+        #[cfg(feature = "parallel")]
+        let result = rayon::iter::ParallelBridge::par_bridge($e);
+
+        #[cfg(not(feature = "parallel"))]
+        let result = $e;
+
+        result
+    }};
+}
 
-use ark_bls12_381::Fq;
 mod vss;
+mod merkle;
+mod reshare;
+mod dkg;
 mod polynomials;
 mod communication;
+mod broadcast;
+mod board;
+mod transcript;
+mod auditor;
 mod rand_extr;
-use vss::{VSS, PubParams};
-use rand_extr::{RandomnessExtractor};
+mod subsets;
+mod subset_family;
+mod metrics;
+mod leakage;
+mod adversary;
+mod scenarios;
+mod party;
+mod error;
+mod role_assignment;
+mod bench;
+mod cli;
 
-fn main() {
-    let t: u64 = 7;
-
-    let pp = PubParams {t: t, n: 3*t + 1, n_parties_total: 5*t+4};
-    let execution_leaks = false;
-    //let vss: VSS<Fq> = VSS { secret: 1.into(), pp: pp, execution_leaks: execution_leaks};
-    //vss.execute();
+use clap::Parser;
 
-    let rand_extr = RandomnessExtractor {t: t as usize}; 
-    rand_extr.execute(); 
-
-    
+fn main() {
+    if let Err(reason) = cli::run(cli::Cli::parse()) {
+        eprintln!("{}", reason);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,167 @@
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use sha2::{Digest, Sha256};
+
+//Leaves and inner nodes are hashed with different prefixes so that no inner node can pass for a leaf.
+//Trees are padded with zero nodes to the next power of two.
+
+const LEAF: u8 = 0;
+const NODE: u8 = 1;
+const PADDING: [u8; 32] = [0u8; 32];
+//Signed roots are prefixed so that a signature on a root is never one on a subshare value
+const ROOT_CONTEXT: &[u8] = b"yosovss merkle root";
+
+fn hash_leaf(leaf: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update([LEAF]).chain_update(leaf).finalize().into()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new().chain_update([NODE]).chain_update(left).chain_update(right).finalize().into()
+}
+
+///Bytes a signer signs to endorse every leaf under `root`
+pub fn root_message(root: &[u8; 32]) -> Vec<u8> {
+    [ROOT_CONTEXT, &root[..]].concat()
+}
+
+///Binary hash tree over a list of byte strings
+pub struct MerkleTree {
+    //levels[0] are the padded leaf hashes, the last level is the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+///Siblings from a leaf up to the root; `index` tells at each level whether the leaf's node is the left or the right child
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerklePath {
+    pub index: u64,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleTree {
+    pub fn new<L: AsRef<[u8]>>(leaves: &[L]) -> MerkleTree {
+        let mut level: Vec<[u8; 32]> = leaves.iter().map(|leaf| hash_leaf(leaf.as_ref())).collect();
+        level.resize(leaves.len().next_power_of_two(), PADDING);
+        let mut levels = vec![level];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1].chunks(2).map(|pair| hash_node(&pair[0], &pair[1])).collect();
+            levels.push(next);
+        }
+        MerkleTree { levels: levels }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    ///Path of leaf `index`, which must be one of the leaves the tree was built from
+    pub fn path(&self, index: usize) -> MerklePath {
+        let siblings = self.levels[..self.levels.len() - 1].iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect();
+        MerklePath { index: index as u64, siblings: siblings }
+    }
+}
+
+impl MerklePath {
+    ///Root of the tree `leaf` sits in if the path is honest
+    pub fn root(&self, leaf: &[u8]) -> [u8; 32] {
+        let mut node = hash_leaf(leaf);
+        for (height, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.index >> height) & 1 == 0 { hash_node(&node, sibling) } else { hash_node(sibling, &node) };
+        }
+        node
+    }
+
+    ///Bytes on the wire: the index, the number of siblings and the siblings
+    pub fn serialized_size(&self) -> u64 {
+        8 + 1 + 32*self.siblings.len() as u64
+    }
+}
+
+///Roots a role has already checked the signature on, by signer key; every further path
+///under a known root costs only hashes. Shared between threads when rows are checked in parallel.
+#[derive(Default)]
+pub struct VerifiedRoots {
+    verified: Mutex<BTreeSet<([u8; 32], [u8; 32])>>,
+}
+
+impl VerifiedRoots {
+    ///Whether `pk` signed `root`, checking the signature only the first time the root comes up
+    pub fn verify(&self, pk: &PublicKey, root: &[u8; 32], signature: &Signature) -> bool {
+        let key = (pk.to_bytes(), *root);
+        if self.verified.lock().is_ok_and(|verified| verified.contains(&key)) {
+            return true;
+        }
+        if pk.verify(&root_message(root), signature).is_err() {
+            return false;
+        }
+        if let Ok(mut verified) = self.verified.lock() {
+            verified.insert(key);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::Signer;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::role_assignment::generate_keypair;
+
+    use super::*;
+
+    fn leaves(count: usize) -> Vec<Vec<u8>> {
+        (0..count).map(|i| vec![i as u8; 3]).collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_membership_only_at_its_own_position() {
+        for count in [1, 2, 3, 5, 8] {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let path = tree.path(index);
+                assert_eq!(path.root(leaf), tree.root());
+                assert_eq!(path.siblings.len(), count.next_power_of_two().trailing_zeros() as usize);
+                if count > 1 {
+                    let moved = MerklePath { index: path.index ^ 1, siblings: path.siblings.clone() };
+                    assert_ne!(moved.root(leaf), tree.root());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn altered_leaves_and_paths_are_rejected() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::new(&leaves);
+        let path = tree.path(2);
+        assert_ne!(path.root(&[9, 9, 9]), tree.root());
+        let mut tampered = path.clone();
+        tampered.siblings[1][0] ^= 1;
+        assert_ne!(tampered.root(&leaves[2]), tree.root());
+        //An inner node is not a leaf: its children's hashes do not verify as a leaf one level up
+        let inner = [tree.levels[0][2], tree.levels[0][3]].concat();
+        let shortened = MerklePath { index: 1, siblings: path.siblings[1..].to_vec() };
+        assert_ne!(shortened.root(&inner), tree.root());
+    }
+
+    #[test]
+    fn a_root_verifies_only_under_its_signer() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let (signer, other) = (generate_keypair(&mut rng), generate_keypair(&mut rng));
+        let root = MerkleTree::new(&leaves(4)).root();
+        let signature = signer.sign(&root_message(&root));
+        let roots = VerifiedRoots::default();
+        assert!(!roots.verify(&other.public, &root, &signature));
+        assert!(!roots.verify(&signer.public, &MerkleTree::new(&leaves(3)).root(), &signature));
+        assert!(roots.verify(&signer.public, &root, &signature));
+        //A signature on the bare root is not one on the root message
+        assert!(!VerifiedRoots::default().verify(&signer.public, &root, &signer.sign(&root)));
+    }
+}
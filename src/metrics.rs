@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+///Role a party plays when it incurs a cost
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Role {
+    Leader,
+    Verifier,
+    Publisher,
+    Dealer,
+    Receiver,
+    Reconstructor,
+    Client,
+}
+
+///Kind of message a communication cost was incurred for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MessageKind {
+    //VSS
+    DealerRow,
+    DoublySignedSubshare,
+    TriplySignedSubshares,
+    Publication,
+    //DKG
+    DealerCommitment,
+    //Resharing
+    Syndromes,
+    //Randomness extractor
+    LeaderValues,
+    ForwardedValues,
+    VerifiedValues,
+    PublishedValues,
+}
+
+///Collector the protocol roles report their costs into
+pub trait Metrics {
+    ///Adds to the cost of `party` acting as `role`
+    fn record(&mut self, party: u64, role: Role, messages: u64, bytes: u64, time: Duration);
+
+    ///Adds `messages` messages of one kind, `bytes` serialized bytes in total, sent by `party` acting as `role`
+    fn record_messages(&mut self, party: u64, role: Role, _kind: MessageKind, messages: u64, bytes: u64) {
+        self.record(party, role, messages, bytes, Duration::ZERO);
+    }
+
+    ///Notes that `party` acting as `role` rejected a message or row from another party because of `error`
+    fn record_rejection(&mut self, _party: u64, _role: Role, _error: &Error) {}
+}
+
+///A message or row a party rejected, and why
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Rejection {
+    pub party: u64,
+    pub role: Role,
+    pub reason: String,
+}
+
+///Number and total serialized size of the messages of one kind
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct MessageCost {
+    pub messages: u64,
+    pub bytes: u64,
+}
+
+///Messages, bytes and CPU time spent by one party in one role
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RoleCost {
+    pub messages: u64,
+    pub bytes: u64,
+    pub time: Duration,
+}
+
+impl RoleCost {
+    fn add(&mut self, other: &RoleCost) {
+        self.messages += other.messages;
+        self.bytes += other.bytes;
+        self.time += other.time;
+    }
+}
+
+//One line of the exported report
+#[derive(Serialize)]
+struct CostRow {
+    party: u64,
+    role: Role,
+    messages: u64,
+    bytes: u64,
+    time_ms: f64,
+}
+
+///Per-party, per-role cost of one protocol run
+#[derive(Clone, Debug, Default)]
+pub struct CostReport {
+    pub costs: BTreeMap<(u64, Role), RoleCost>,
+    pub by_kind: BTreeMap<MessageKind, MessageCost>,
+    pub rejections: Vec<Rejection>,
+}
+
+impl Metrics for CostReport {
+    fn record(&mut self, party: u64, role: Role, messages: u64, bytes: u64, time: Duration) {
+        self.costs.entry((party, role)).or_default().add(&RoleCost { messages: messages, bytes: bytes, time: time });
+    }
+
+    fn record_messages(&mut self, party: u64, role: Role, kind: MessageKind, messages: u64, bytes: u64) {
+        self.record(party, role, messages, bytes, Duration::ZERO);
+        let cost = self.by_kind.entry(kind).or_default();
+        cost.messages += messages;
+        cost.bytes += bytes;
+    }
+
+    fn record_rejection(&mut self, party: u64, role: Role, error: &Error) {
+        self.rejections.push(Rejection { party: party, role: role, reason: error.to_string() });
+    }
+}
+
+impl CostReport {
+    pub fn get(&self, party: u64, role: Role) -> RoleCost {
+        self.costs.get(&(party, role)).copied().unwrap_or_default()
+    }
+
+    ///Costs of all parties acting in the given role
+    pub fn by_role(&self, role: Role) -> Vec<RoleCost> {
+        self.costs.iter().filter(|((_, r), _)| *r == role).map(|(_, cost)| *cost).collect()
+    }
+
+    ///Cost of each party summed over all its roles
+    pub fn per_party(&self) -> BTreeMap<u64, RoleCost> {
+        let mut per_party: BTreeMap<u64, RoleCost> = BTreeMap::new();
+        for ((party, _), cost) in &self.costs {
+            per_party.entry(*party).or_default().add(cost);
+        }
+        per_party
+    }
+
+    pub fn total(&self) -> RoleCost {
+        let mut total = RoleCost::default();
+        for cost in self.costs.values() {
+            total.add(cost);
+        }
+        total
+    }
+
+    fn rows(&self) -> Vec<CostRow> {
+        self.costs.iter()
+            .map(|((party, role), cost)| CostRow {
+                party: *party,
+                role: *role,
+                messages: cost.messages,
+                bytes: cost.bytes,
+                time_ms: cost.time.as_secs_f64() * 1000.0,
+            })
+            .collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("party,role,messages,bytes,time_ms\n");
+        for row in self.rows() {
+            csv.push_str(&format!("{},{:?},{},{},{}\n", row.party, row.role, row.messages, row.bytes, row.time_ms));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.rows()).unwrap()
+    }
+
+    pub fn by_kind_to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.by_kind).unwrap()
+    }
+
+    ///Prints per-party time and MB like the VSS does, followed by the overall figures
+    pub fn print_per_party(&self) {
+        println!("party | time ms | comm MB | messages");
+        for (party, cost) in self.per_party() {
+            println!("{} | {} | {} | {}", party, cost.time.as_millis(), (cost.bytes as f64)/1000000.0, cost.messages);
+        }
+        let total = self.total();
+        println!("Overall time: {}", total.time.as_millis());
+        println!("Overall comm: {}", (total.bytes as f64)/1000000.0);
+    }
+
+    pub fn print_by_kind(&self) {
+        println!("message | messages | comm MB");
+        for (kind, cost) in &self.by_kind {
+            println!("{:?} | {} | {}", kind, cost.messages, (cost.bytes as f64)/1000000.0);
+        }
+    }
+}
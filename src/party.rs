@@ -0,0 +1,55 @@
+use std::collections::BTreeSet;
+
+use ark_ff::{Field, PrimeField};
+use ed25519_dalek::PublicKey;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+
+///Opaque party identifier, the hash of the party's public key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PartyId([u8; 32]);
+
+impl PartyId {
+    pub fn from_public_key(pk: &PublicKey) -> PartyId {
+        PartyId(Sha256::digest(pk.as_bytes()).into())
+    }
+
+    //Candidate evaluation point; the counter is bumped when a candidate is zero or already taken
+    fn candidate_point<F: Field>(&self, counter: u32) -> F {
+        let digest = Sha256::new().chain_update(self.0).chain_update(counter.to_le_bytes()).finalize();
+        F::from_base_prime_field(F::BasePrimeField::from_le_bytes_mod_order(&digest))
+    }
+}
+
+///Distinct non-zero evaluation points for the given parties, in the same order.
+///Zero is excluded because the secret, or the first of several packed ones, sits at x = 0.
+pub fn evaluation_points<F: Field>(ids: &[PartyId]) -> Result<Vec<F>> {
+    let distinct_ids: BTreeSet<&PartyId> = ids.iter().collect();
+    if distinct_ids.len() != ids.len() {
+        return Err(Error::InvalidParams("the same party appears twice".to_string()));
+    }
+
+    let mut taken: Vec<F> = Vec::with_capacity(ids.len());
+    for id in ids {
+        let mut counter = 0;
+        let mut point: F = id.candidate_point(counter);
+        while point == F::ZERO || taken.contains(&point) {
+            counter += 1;
+            point = id.candidate_point(counter);
+        }
+        taken.push(point);
+    }
+    Ok(taken)
+}
+
+///Evaluation points of the parties owning the given public keys
+pub fn evaluation_points_of<F: Field>(pks: &[PublicKey]) -> Result<Vec<F>> {
+    let ids: Vec<PartyId> = pks.iter().map(PartyId::from_public_key).collect();
+    evaluation_points(&ids)
+}
+
+///Points the secrets of a dealing sit at: 0 for a single secret, then -1, -2, ... for further packed ones
+pub fn secret_points<F: Field>(packing: u64) -> Vec<F> {
+    (0..packing).map(|k| -F::from(k)).collect()
+}
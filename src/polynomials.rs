@@ -1,21 +1,47 @@
 use std::{collections::BTreeMap};
+use std::ops::{Add, Mul};
 
-use ark_ff::{Field};
-use ark_std::rand::Rng;
+use ark_ff::Field;
+use rand::Rng;
 /// Symmetric bi-variate polynomial
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SymBVPoly<F: Field> {
     pub coeffs: BTreeMap<(u64, u64), F>,
     pub degree: u64,
 }
 
+///Why a set of points could not be interpolated
+#[derive(Clone, Debug, PartialEq)]
+pub enum InterpolationError {
+    NoPoints,
+    LengthMismatch { x: usize, y: usize },
+    ///Positions of two equal x-coordinates
+    DuplicatePoint(usize, usize),
+    TooFewPoints { points: usize, needed: usize },
+    ///More than `max` values are off every polynomial of the degree
+    TooManyErrors { max: usize },
+}
+
+impl std::fmt::Display for InterpolationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InterpolationError::NoPoints => write!(f, "no points to interpolate"),
+            InterpolationError::LengthMismatch { x, y } => write!(f, "{} x-coordinates but {} values", x, y),
+            InterpolationError::DuplicatePoint(i, j) => write!(f, "x-coordinates {} and {} are equal", i, j),
+            InterpolationError::TooFewPoints { points, needed } => write!(f, "{} points, {} are needed", points, needed),
+            InterpolationError::TooManyErrors { max } => write!(f, "more than {} values are off the polynomial", max),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Poly<F: Field> {
     pub coeffs: Vec<F>,
     pub degree: u64,
 }
 impl<F: Field> SymBVPoly<F> {
     pub fn rand<R: Rng>(d: u64, rng: &mut R) -> SymBVPoly<F> {
-        let mut coeffs: BTreeMap<(u64, u64), F> = term_powers_for_degree(d)
+        let coeffs: BTreeMap<(u64, u64), F> = term_powers_for_degree(d)
         .filter(|(dx, dy)| dx >= dy)
         .map(|(dx, dy)| ((dx, dy), F::rand(rng)))
         .collect();
@@ -26,29 +52,12 @@ impl<F: Field> SymBVPoly<F> {
         }
     }
 
-    pub fn rand_new<R: Rng>(d: u64, rng: &mut R) -> SymBVPoly<F> {
-        let mut coeffs: BTreeMap<(u64, u64), F> = Default::default();
-        for deg_x in 0..=d/2 {
-            for deg_y in 0..=d {
-                let value =F::rand(rng); 
-                coeffs.insert((deg_x, deg_y), value);
-                if deg_x != deg_y {
-                    coeffs.insert((deg_y, deg_x), value);
-                }
-            }
-        }
-        SymBVPoly {
-            coeffs: coeffs,
-            degree: d,
-        }
-    }
-
     pub fn eval(&self, x: F, y: F) -> F {
         let mut result = F::ZERO;
         for deg_x in (0..=self.degree).rev() {
             let mut result_intermediate = self.coeffs[&(self.degree, deg_x)]; //Note that deg_x always <= self.degree, hence the switch
             for deg_y in (1..=self.degree).rev() {
-                if (deg_x >= (deg_y-1)) {
+                if deg_x >= deg_y-1 {
                     result_intermediate = self.coeffs[&(deg_x,deg_y-1)] + y*result_intermediate;
                 } else {
                     result_intermediate = self.coeffs[&(deg_y-1,deg_x)] + y*result_intermediate;
@@ -58,43 +67,9 @@ impl<F: Field> SymBVPoly<F> {
         }
         result
     }
-
-    pub fn eval_std(&self, x: F, y: F) -> F {
-        let mut result = F::ZERO;
-        for deg_x in (0..=self.degree).rev() {
-            let mut result_intermediate = F::ZERO;
-            for deg_y in (0..=self.degree).rev() {
-                if (deg_x >= deg_y) {
-                    result_intermediate += self.coeffs[&(deg_x, deg_y)] * y.pow([deg_y]);
-                } else {
-                    result_intermediate += self.coeffs[&(deg_y, deg_x)] * y.pow([deg_y]);
-                }
-            }
-            result = result_intermediate + x*result;
-        }
-        result
-    }
-
-    pub fn eval_new(&self, x: F, y: F) -> F {
-        let mut result = F::ZERO;
-        for deg_x in (0..=self.degree).rev() {
-            for deg_y in (0..=self.degree).rev() {
-                result = (result + self.coeffs[&(deg_x, deg_y)])*x + y*self.coeffs[&(deg_x, deg_y)];
-            }
-        }
-        result
-    }
 }
 
 impl<F: Field> Poly<F> {
-    pub fn eval_std(&self, x: F) -> F {
-        let mut result = F::ZERO;
-        for deg_x in 0..=self.degree {
-            result += self.coeffs[deg_x as usize] * x.pow([deg_x]);
-        }
-        result
-    }
-
     pub fn eval(&self, x: F) -> F {
         let mut result = self.coeffs[self.degree as usize];
         for deg_x in (1..=self.degree).rev() {
@@ -103,7 +78,20 @@ impl<F: Field> Poly<F> {
         result
     }
 
-    pub fn evals_to_coeffs(x: &Vec<u64>, y: &Vec<F>, n: u64) -> Poly<F> {
+    ///Lagrange interpolation through the points (x[i], y[i]); the x-coordinates must be distinct
+    pub fn evals_to_coeffs(x: &[F], y: &[F]) -> Result<Poly<F>, InterpolationError> {
+        if x.len() != y.len() {
+            return Err(InterpolationError::LengthMismatch { x: x.len(), y: y.len() });
+        }
+        if x.is_empty() {
+            return Err(InterpolationError::NoPoints);
+        }
+        for i in 0..x.len() {
+            if let Some(j) = (i + 1..x.len()).find(|j| x[*j] == x[i]) {
+                return Err(InterpolationError::DuplicatePoint(i, j));
+            }
+        }
+        let n = x.len() as u64;
         let mut full_coeffs: Vec<F> = vec![F::ZERO; n as usize];
         let mut terms: Vec<F> = vec![F::ZERO; n as usize];
 
@@ -120,7 +108,7 @@ impl<F: Field> Poly<F> {
                 if i == j {
                     continue;
                 } 
-                prod *= F::from(x[i as usize]) - F::from(x[j as usize]);
+                prod *= x[i as usize] - x[j as usize];
             }
 
             prod = y[i as usize] / prod;
@@ -135,7 +123,7 @@ impl<F: Field> Poly<F> {
                     let tmp_term = terms[(k - 1) as usize];
                     //dbg!(k, tmp_term);
                     terms[k as usize] += tmp_term;
-                    terms[(k - 1) as usize] *= -F::from(x[j as usize]);
+                    terms[(k - 1) as usize] *= -x[j as usize];
                 }
             }
 
@@ -155,17 +143,246 @@ impl<F: Field> Poly<F> {
             }
         }
 
-        Poly {
+        Ok(Poly {
             degree: degree,
             coeffs: full_coeffs
+        })
+
+    }
+}
+
+
+
+impl<F: Field> Poly<F> {
+    //Degree of the highest non-zero coefficient, 0 for the zero polynomial
+    fn from_coeffs(coeffs: Vec<F>) -> Poly<F> {
+        let degree = coeffs.iter().rposition(|coeff| *coeff != F::ZERO).unwrap_or(0) as u64;
+        Poly { coeffs: coeffs, degree: degree }
+    }
+}
+
+impl<F: Field> SymBVPoly<F> {
+    ///The univariate row f(x, y) at fixed x, as a polynomial in y
+    pub fn row(&self, x: F) -> Poly<F> {
+        let mut coeffs: Vec<F> = vec![F::ZERO; (self.degree + 1) as usize];
+        let mut x_power = F::ONE;
+        for deg_x in 0..=self.degree {
+            for deg_y in 0..=self.degree {
+                //Only coefficients with deg_x >= deg_y are stored
+                let coeff = self.coeffs[&(deg_x.max(deg_y), deg_x.min(deg_y))];
+                coeffs[deg_y as usize] += coeff * x_power;
+            }
+            x_power *= x;
         }
+        Poly::from_coeffs(coeffs)
+    }
 
+    ///p(x)·p(y), the symmetric polynomial whose rows are all multiples of p
+    pub fn outer(p: &Poly<F>) -> SymBVPoly<F> {
+        let coeffs = term_powers_for_degree(p.degree)
+            .filter(|(dx, dy)| dx >= dy)
+            .map(|(dx, dy)| ((dx, dy), p.coeffs[dx as usize] * p.coeffs[dy as usize]))
+            .collect();
+        SymBVPoly { coeffs: coeffs, degree: p.degree }
+    }
+
+    ///Random symmetric polynomial of degree d with f(points[k], points[k]) = values[k], for at most d+1 distinct points
+    pub fn rand_with_diagonal<R: Rng>(d: u64, points: &[F], values: &[F], rng: &mut R) -> Result<SymBVPoly<F>, InterpolationError> {
+        if points.len() != values.len() {
+            return Err(InterpolationError::LengthMismatch { x: points.len(), y: values.len() });
+        }
+        let mut poly = SymBVPoly::rand(d, rng);
+        //Adding c·L_k(x)·L_k(y) for the Lagrange basis polynomial L_k of the points moves the k-th diagonal value only
+        for k in 0..points.len() {
+            let unit: Vec<F> = (0..points.len()).map(|j| if j == k { F::ONE } else { F::ZERO }).collect();
+            let basis = Poly::evals_to_coeffs(points, &unit)?;
+            let correction = values[k] - poly.eval(points[k], points[k]);
+            poly = poly + SymBVPoly::outer(&basis) * correction;
+        }
+        Ok(poly)
+    }
+
+    //Coefficient map of degree d with every stored term present, as `eval` expects
+    fn padded_coeffs(&self, d: u64) -> BTreeMap<(u64, u64), F> {
+        term_powers_for_degree(d)
+            .filter(|(dx, dy)| dx >= dy)
+            .map(|term| (term, self.coeffs.get(&term).copied().unwrap_or(F::ZERO)))
+            .collect()
+    }
+}
+
+impl<F: Field> Add for SymBVPoly<F> {
+    type Output = SymBVPoly<F>;
+
+    fn add(self, other: SymBVPoly<F>) -> SymBVPoly<F> {
+        let degree = self.degree.max(other.degree);
+        let mut coeffs = self.padded_coeffs(degree);
+        for (term, coeff) in other.padded_coeffs(degree) {
+            *coeffs.get_mut(&term).unwrap() += coeff;
+        }
+        SymBVPoly { coeffs: coeffs, degree: degree }
     }
 }
 
+impl<F: Field> Mul<F> for SymBVPoly<F> {
+    type Output = SymBVPoly<F>;
 
+    fn mul(self, scalar: F) -> SymBVPoly<F> {
+        SymBVPoly { coeffs: self.coeffs.into_iter().map(|(term, coeff)| (term, coeff * scalar)).collect(), degree: self.degree }
+    }
+}
 
 fn term_powers_for_degree(d: u64) -> impl Iterator<Item = (u64, u64)> {
     (0..=d)
     .flat_map(move |deg_x| (0..=d).map(move |deg_y| (deg_x, deg_y)))
-}
\ No newline at end of file
+}
+//Brings `rows` to reduced row echelon form over their first `columns` entries and returns the pivot column of each non-zero row
+fn reduce<F: Field>(rows: &mut [Vec<F>], columns: usize) -> Vec<usize> {
+    let mut pivots: Vec<usize> = Vec::new();
+    for column in 0..columns {
+        let rank = pivots.len();
+        let pivot = match (rank..rows.len()).find(|r| !rows[*r][column].is_zero()) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        let inverse = rows[rank][column].inverse().unwrap();
+        for entry in rows[rank].iter_mut() {
+            *entry *= inverse;
+        }
+        let pivot_row = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[column];
+            if r != rank && !factor.is_zero() {
+                for (entry, pivot_entry) in row.iter_mut().zip(&pivot_row) {
+                    *entry -= factor * pivot_entry;
+                }
+            }
+        }
+        pivots.push(column);
+    }
+    pivots
+}
+
+///Number of linearly independent vectors among `rows`, which all have the same length
+pub fn rank<F: Field>(mut rows: Vec<Vec<F>>) -> usize {
+    let columns = rows.first().map_or(0, |row| row.len());
+    reduce(&mut rows, columns).len()
+}
+
+//Some x with rows·x = rhs, or None if there is none
+fn solve<F: Field>(rows: &[Vec<F>], rhs: &[F]) -> Option<Vec<F>> {
+    let columns = rows.first().map_or(0, |row| row.len());
+    let mut augmented: Vec<Vec<F>> = rows.iter().zip(rhs).map(|(row, value)| row.iter().chain(std::iter::once(value)).copied().collect()).collect();
+    let pivots = reduce(&mut augmented, columns + 1);
+    if pivots.contains(&columns) {
+        return None;
+    }
+    let mut solution = vec![F::ZERO; columns];
+    for (row, column) in pivots.into_iter().enumerate() {
+        solution[column] = augmented[row][columns];
+    }
+    Some(solution)
+}
+
+impl<F: Field> Poly<F> {
+    ///Berlekamp-Welch decoding: the polynomial of degree at most `degree` through all points (x[i], y[i]) but at most
+    ///(len - degree - 1)/2 of them, and the positions of the values that are off it
+    pub fn decode(x: &[F], y: &[F], degree: u64) -> Result<(Poly<F>, Vec<usize>), InterpolationError> {
+        if x.len() != y.len() {
+            return Err(InterpolationError::LengthMismatch { x: x.len(), y: y.len() });
+        }
+        let needed = degree as usize + 1;
+        if x.len() < needed {
+            return Err(InterpolationError::TooFewPoints { points: x.len(), needed: needed });
+        }
+        let max_errors = (x.len() - needed) / 2;
+
+        //Q(x_i) = y_i·E(x_i) for the monic error locator E of degree max_errors and Q of degree max_errors + degree
+        let equations: Vec<Vec<F>> = x.iter().zip(y)
+            .map(|(x_i, y_i)| {
+                let locator = (0..max_errors).map(|l| -*y_i * x_i.pow([l as u64]));
+                let quotient = (0..=max_errors + degree as usize).map(|q| x_i.pow([q as u64]));
+                locator.chain(quotient).collect()
+            })
+            .collect();
+        let rhs: Vec<F> = x.iter().zip(y).map(|(x_i, y_i)| *y_i * x_i.pow([max_errors as u64])).collect();
+        let locator = solve(&equations, &rhs).ok_or(InterpolationError::TooManyErrors { max: max_errors })?;
+        let locate = |x_i: &F| locator[..max_errors].iter().rev().fold(F::ONE, |value, coeff| value * x_i + coeff);
+
+        //Every error is a root of E, so the points off its roots lie on the polynomial
+        let (kept_x, kept_y): (Vec<F>, Vec<F>) = x.iter().zip(y).filter(|(x_i, _)| !locate(x_i).is_zero()).take(needed).unzip();
+        if kept_x.len() < needed {
+            return Err(InterpolationError::TooManyErrors { max: max_errors });
+        }
+        let poly = Poly::evals_to_coeffs(&kept_x, &kept_y)?;
+        let errors: Vec<usize> = x.iter().zip(y).enumerate().filter(|(_, (x_i, y_i))| poly.eval(**x_i) != **y_i).map(|(i, _)| i).collect();
+        if errors.len() > max_errors {
+            return Err(InterpolationError::TooManyErrors { max: max_errors });
+        }
+        Ok((poly, errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    use super::*;
+
+    fn points() -> Vec<Fr> {
+        [0u64, 1, 2, 7, 19].iter().map(|x| Fr::from(*x)).chain([-Fr::from(3u64)]).collect()
+    }
+
+    #[test]
+    fn rows_match_pointwise_evaluation() {
+        let mut rng = test_rng();
+        let poly: SymBVPoly<Fr> = SymBVPoly::rand(4, &mut rng);
+        for x in points() {
+            let row = poly.row(x);
+            assert!(row.degree <= 4);
+            for y in points() {
+                assert_eq!(row.eval(y), poly.eval(x, y));
+                assert_eq!(poly.eval(x, y), poly.eval(y, x));
+            }
+        }
+    }
+
+    #[test]
+    fn arithmetic_matches_pointwise_evaluation() {
+        let mut rng = test_rng();
+        let f: SymBVPoly<Fr> = SymBVPoly::rand(3, &mut rng);
+        let g: SymBVPoly<Fr> = SymBVPoly::rand(5, &mut rng);
+        let scalar = Fr::from(42u64);
+        let sum = f.clone() + g.clone();
+        let scaled = f.clone() * scalar;
+        assert_eq!(sum.degree, 5);
+        for x in points() {
+            for y in points() {
+                assert_eq!(sum.eval(x, y), f.eval(x, y) + g.eval(x, y));
+                assert_eq!(scaled.eval(x, y), f.eval(x, y) * scalar);
+            }
+        }
+
+        let p = Poly::evals_to_coeffs(&points()[..3], &[Fr::from(5u64), Fr::from(9u64), Fr::from(2u64)]).unwrap();
+        let outer = SymBVPoly::outer(&p);
+        for x in points() {
+            for y in points() {
+                assert_eq!(outer.eval(x, y), p.eval(x) * p.eval(y));
+            }
+        }
+    }
+
+    #[test]
+    fn the_diagonal_holds_the_requested_values() {
+        let mut rng = test_rng();
+        let diagonal_points = [Fr::from(0u64), -Fr::from(1u64), -Fr::from(2u64)];
+        let values = [Fr::from(10u64), Fr::from(20u64), Fr::from(30u64)];
+        let poly: SymBVPoly<Fr> = SymBVPoly::rand_with_diagonal(4, &diagonal_points, &values, &mut rng).unwrap();
+        for (point, value) in diagonal_points.iter().zip(values) {
+            assert_eq!(poly.eval(*point, *point), value);
+        }
+        assert!(SymBVPoly::<Fr>::rand_with_diagonal(4, &diagonal_points, &values[..2], &mut rng).is_err());
+    }
+}
@@ -1,25 +1,168 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::subsets::{binomial, leader_subsets, participation_subsets, unrank};
+use crate::subset_family::{analyze, SubsetFamily};
+use crate::metrics::{CostReport, MessageKind, Metrics, Role};
+use crate::adversary::{Adversary, Honest};
+use crate::error::{Error, Result};
+use crate::transcript::{Deviation, Protocol, Transcript, Verification};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use std::time::{Duration, SystemTime};
+
+//Corruption sets drawn when a family is too large to analyze exactly
+const ANALYSIS_SAMPLES: usize = 100_000;
+
+///Rule that picks which member of a subset leads it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LeaderRule {
+    ///The smallest party index in the subset
+    LowestIndex,
+    ///Members take turns according to the subset index
+    RoundRobin,
+    ///A member picked by hashing the subset with SHA-256, the same on every platform and build
+    Hashed,
+}
 
-use std::time::SystemTime;
-use std::mem::size_of_val;
-
-pub struct Verifier {
+///Shape of the subset family: k-subsets of n parties, each led by one member
+#[derive(Clone, Debug)]
+pub struct ExtractorParams {
+    //Number of parties acting as verifiers and publishers
+    pub n: usize,
+    //Size of each subset
+    pub k: usize,
+    //Number of potentially adversarial parties the family has to tolerate
     pub t: usize,
+    pub leader_rule: LeaderRule,
+    pub family: SubsetFamily,
+}
+
+impl ExtractorParams {
+    ///The original family: all (2t+1)-subsets of 3t+1 parties, led by their lowest index
+    pub fn new(t: usize) -> ExtractorParams {
+        ExtractorParams { n: 3*t + 1, k: 2*t + 1, t: t, leader_rule: LeaderRule::LowestIndex, family: SubsetFamily::Complete }
+    }
+
+    ///Number of subsets in the family, saturating at usize::MAX for complete families `validate` rejects
+    pub fn number_subsets(&self) -> usize {
+        match &self.family {
+            SubsetFamily::Complete => binomial(self.n, self.k).unwrap_or(usize::MAX),
+            SubsetFamily::Explicit(subsets) => subsets.len(),
+        }
+    }
+
+    ///Checks that whichever t parties are corrupted, some subset is fully honest and therefore honest-led.
+    ///For explicit families this is exact when the corruption sets can be enumerated, otherwise ANALYSIS_SAMPLES random
+    ///corruption sets and a greedy search look for a counterexample.
+    pub fn validate(&self) -> Result<()> {
+        if self.k == 0 || self.k > self.n {
+            return Err(Error::InvalidParams(format!("subset size {} must be between 1 and the number of parties {}", self.k, self.n)));
+        }
+        if self.t >= self.n {
+            return Err(Error::InvalidParams(format!("corruption threshold {} must be smaller than the number of parties {}", self.t, self.n)));
+        }
+        //A fully honest subset exists iff the n-t honest parties can fill one, and its leader is one of its members
+        if self.n - self.t < self.k {
+            return Err(Error::InvalidParams(format!("with {} of {} parties corrupted no {}-subset is guaranteed to be fully honest", self.t, self.n, self.k)));
+        }
+        if let (SubsetFamily::Complete, None) = (&self.family, binomial(self.n, self.k)) {
+            return Err(Error::InvalidParams(format!("the {}-subsets of {} parties are too many to index", self.k, self.n)));
+        }
+        if let SubsetFamily::Explicit(subsets) = &self.family {
+            for subset in subsets.iter() {
+                if subset.len() != self.k || subset.windows(2).any(|pair| pair[0] >= pair[1]) || subset[0] < 1 || subset[self.k - 1] > self.n {
+                    return Err(Error::InvalidParams(format!("{:?} is not a sorted {}-subset of 1..={}", subset, self.k, self.n)));
+                }
+            }
+            if let Some(controlling_set) = analyze(subsets, self.n, self.t, ANALYSIS_SAMPLES, 0).controlling_set {
+                return Err(Error::InvalidParams(format!("corrupting parties {:?} controls every subset of the family", controlling_set)));
+            }
+        }
+        Ok(())
+    }
+
+    ///Number of subsets that stay fully honest in the worst case, i.e. C(n-t, k), if known
+    pub fn guaranteed_honest_subsets(&self) -> Option<usize> {
+        match &self.family {
+            SubsetFamily::Complete => binomial(self.n.saturating_sub(self.t), self.k),
+            SubsetFamily::Explicit(_) => None,
+        }
+    }
+
+    pub fn leader_of(&self, subset_index: usize, subset: &[usize]) -> usize {
+        match self.leader_rule {
+            LeaderRule::LowestIndex => subset[0],
+            LeaderRule::RoundRobin => subset[subset_index % subset.len()],
+            LeaderRule::Hashed => {
+                let mut hasher = Sha256::new().chain_update(b"yosowcc-subset-leader");
+                for party in subset {
+                    hasher.update((*party as u64).to_le_bytes());
+                }
+                let digest = hasher.finalize();
+                let draw = u64::from_le_bytes(digest[..8].try_into().unwrap());
+                subset[(draw % subset.len() as u64) as usize]
+            }
+        }
+    }
+
+    ///Subsets led by the given party
+    pub fn leader_subsets(&self, leader: usize) -> Box<dyn Iterator<Item = (usize, Vec<usize>)> + Send> {
+        match (&self.family, self.leader_rule) {
+            //Lowest-index leadership of the complete family is a contiguous block that can be enumerated directly
+            (SubsetFamily::Complete, LeaderRule::LowestIndex) => Box::new(leader_subsets(self.n, self.k, leader)),
+            _ => {
+                let params = self.clone();
+                Box::new(self.participation_subsets(leader)
+                            .filter(move |(subset_index, subset)| params.leader_of(*subset_index, subset) == leader))
+            }
+        }
+    }
+
+    ///Subsets the given party is a member of
+    pub fn participation_subsets(&self, party: usize) -> Box<dyn Iterator<Item = (usize, Vec<usize>)> + Send> {
+        match &self.family {
+            SubsetFamily::Complete => Box::new(participation_subsets(self.n, self.k, party)),
+            SubsetFamily::Explicit(subsets) => {
+                let subsets = subsets.clone();
+                Box::new((0..subsets.len())
+                            .filter_map(move |subset_index| {
+                                let subset = &subsets[subset_index];
+                                if subset.contains(&party) { Some((subset_index, subset.clone())) } else { None }
+                            }))
+            }
+        }
+    }
+
+    pub fn subset(&self, subset_index: usize) -> Vec<usize> {
+        match &self.family {
+            SubsetFamily::Complete => unrank(self.n, self.k, subset_index),
+            SubsetFamily::Explicit(subsets) => subsets[subset_index].clone(),
+        }
+    }
+}
+
+pub struct Verifier<'a> {
+    pub params: &'a ExtractorParams,
     pub index: usize,
-    pub all_subsets: Vec<Vec<usize>>,
-    pub my_leader_subsets: BTreeMap<usize, Vec<usize>>,
-    pub my_participation_subsets: BTreeMap<usize, Vec<usize>>,
+    //If set, subset values are derived from this seed instead of fresh randomness
+    pub seed: Option<u64>,
     pub values_of_participation_subsets: BTreeMap<usize, i32>,
-    pub agreeable_parties_for_participation_subsets: BTreeMap<usize, Vec<usize>>
+    pub agreeable_parties_for_participation_subsets: BTreeMap<usize, Vec<usize>>,
+    //Forwarded values that contradicted the leader's
+    pub complaints: u64,
+    //Parties that sent values which cannot come from an honest party
+    pub faulty: BTreeSet<usize>
 }
 
-pub struct Publisher {
-    pub t: usize,
-    pub index: usize,
-    pub all_subsets: Vec<Vec<usize>>,
-    pub my_subsets: BTreeMap<usize, Vec<usize>>,
-    pub received_values_of_participation_subsets: BTreeMap<usize, BTreeMap<usize, i32>>
+pub struct Publisher<'a> {
+    pub params: &'a ExtractorParams,
+    pub index: usize
 }
 
 pub struct Client {
@@ -27,13 +170,53 @@ pub struct Client {
 }
 
 pub struct RandomnessExtractor {
-    pub t: usize,
+    pub params: ExtractorParams,
+    pub seed: Option<u64>,
+}
+
+///Coin, running time and per-party costs of one extractor run
+pub struct ExtractorOutcome {
+    pub coin: i32,
+    pub time: Duration,
+    pub report: CostReport,
+    //Complaints raised by each verifier
+    pub complaints: BTreeMap<usize, u64>,
+    //Subset results published by each publisher
+    pub published: BTreeMap<usize, BTreeMap<usize, i32>>,
+    //Parties each verifier marked as faulty
+    pub faulty: BTreeMap<usize, BTreeSet<usize>>,
 }
 
 impl RandomnessExtractor {
-    pub fn execute(&self) {
-        let t = self.t;
-        let mut comm_overall = 0.0;
+    pub fn execute(&self) -> Result<ExtractorOutcome> {
+        self.execute_against(&mut Honest)
+    }
+
+    ///Runs the extractor with the parties `adversary` corrupts following its behavior
+    pub fn execute_against<A: Adversary>(&self, adversary: &mut A) -> Result<ExtractorOutcome> {
+        self.run(adversary, None)
+    }
+
+    ///Like execute_against, and also returns the transcript of every message and the coin
+    pub fn execute_recorded<A: Adversary>(&self, adversary: &mut A) -> Result<(ExtractorOutcome, Transcript)> {
+        let params = &self.params;
+        let subsets = match &params.family {
+            SubsetFamily::Complete => None,
+            SubsetFamily::Explicit(subsets) => Some(subsets.to_vec()),
+        };
+        let mut transcript = Transcript::new(Protocol::Extractor { n: params.n, k: params.k, t: params.t, leader_rule: params.leader_rule, subsets: subsets });
+        let outcome = self.run(adversary, Some(&mut transcript))?;
+        transcript.output = outcome.coin.to_le_bytes().to_vec();
+        Ok((outcome, transcript))
+    }
+
+    fn run<A: Adversary>(&self, adversary: &mut A, mut transcript: Option<&mut Transcript>) -> Result<ExtractorOutcome> {
+        let params = &self.params;
+        let n = params.n;
+        params.validate()?;
+        let mut report = CostReport::default();
+        let mut complaints: BTreeMap<usize, u64> = BTreeMap::new();
+        let mut faulty: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
         let start_time = SystemTime::now();
         //mesages to send to verifier: first to which verifier/publisher, then from which leader/verifier, then which subset and finally the random value
         let mut messages_to_send_to_verifiers_from_leaders: BTreeMap<usize, BTreeMap<usize, BTreeMap<usize, i32>>> = Default::default();
@@ -41,355 +224,498 @@ impl RandomnessExtractor {
         let mut messages_to_send_to_publishers: BTreeMap<usize, BTreeMap<usize, BTreeMap<usize, i32>>> = Default::default();
         let mut messages_send_by_publishers: BTreeMap<usize, BTreeMap<usize, i32>> = Default::default();
 
-        for i in 1..=3*t + 1 {
-            messages_to_send_to_verifiers_from_leaders.insert(i, BTreeMap::new());
-            messages_to_send_to_verifiers_from_verifiers.insert(i, BTreeMap::new());
-            messages_to_send_to_publishers.insert(i, BTreeMap::new());
-            messages_send_by_publishers.insert(i, BTreeMap::new());
-
+        //Leading phase: every party that leads some subset sends the subset's random value to all its members.
+        //Leaders speak before all verifiers, so any member of a subset may lead it.
+        for i in 1..=n {
+            let verifier = Verifier {params: params, 
+                                                index: i, 
+                                                seed: self.seed, 
+                                                values_of_participation_subsets : BTreeMap::new(), 
+                                                agreeable_parties_for_participation_subsets: BTreeMap::new(),
+                                                complaints: 0,
+                                                faulty: BTreeSet::new()};
+            let leader_start_time = SystemTime::now();
+            let mut messages_to_send_to_verifiers_as_leader = verifier.lead();
+            if adversary.is_corrupted(i as u64) {
+                adversary.tamper_leader_values(i as u64, &mut messages_to_send_to_verifiers_as_leader);
+            }
+            if let Some(transcript) = transcript.as_deref_mut() {
+                record_values(transcript, MessageKind::LeaderValues, i, &messages_to_send_to_verifiers_as_leader)?;
+            }
+            let leader_time = SystemTime::now().duration_since(leader_start_time).unwrap();
+            if messages_to_send_to_verifiers_as_leader.values().all(|messages| messages.is_empty()) {
+                continue;
+            }
+            let (messages, bytes) = point_to_point_cost(i, &messages_to_send_to_verifiers_as_leader);
+            report.record(i as u64, Role::Leader, 0, 0, leader_time);
+            report.record_messages(i as u64, Role::Leader, MessageKind::LeaderValues, messages, bytes);
+
+            for (verifier_index, messages_to_this_verifier_from_this_leader) in messages_to_send_to_verifiers_as_leader {
+                messages_to_send_to_verifiers_from_leaders.entry(verifier_index)
+                                                        .or_default()
+                                                        .insert(i, messages_to_this_verifier_from_this_leader);
+            }
         }
 
-        for i in 1..=3*t +1 {
-            let mut verifier = Verifier {t: t, 
-                                                index: dbg!(i), 
-                                                all_subsets: Vec::new(), 
-                                                my_leader_subsets: BTreeMap::new(), 
-                                                my_participation_subsets: BTreeMap::new(), 
+        for i in 1..=n {
+            let mut verifier = Verifier {params: params, 
+                                                index: i, 
+                                                seed: self.seed, 
                                                 values_of_participation_subsets : BTreeMap::new(), 
-                                                agreeable_parties_for_participation_subsets: BTreeMap::new()};
-            verifier.init();
-
-            //If party is one of the leaders, we compute messages it sends to other verifiers as the leader
-            if i < t + 2 {
-                let messages_to_send_to_verifiers_as_leader = verifier.lead();
-                comm_overall += (4.0*(messages_to_send_to_verifiers_as_leader.len() as f64) *
-                (messages_to_send_to_verifiers_as_leader.values().last().unwrap().len() as f64))/1000000.0;
-
-                for future_verifier in i..=3*t+1 {
-                    let messages_to_this_verifier_from_this_leader = messages_to_send_to_verifiers_as_leader.get(&future_verifier).unwrap();
-                    messages_to_send_to_verifiers_from_leaders.get_mut(&future_verifier)
-                                                            .unwrap()
-                                                            .insert(i, messages_to_this_verifier_from_this_leader.clone());
-                }
-            }
+                                                agreeable_parties_for_participation_subsets: BTreeMap::new(),
+                                                complaints: 0,
+                                                faulty: BTreeSet::new()};
 
-            let mut leader_messages_to_forward_to_verifiers_from_verifier: BTreeMap<usize, BTreeMap<usize, i32>> = Default::default();
+            let verifier_start_time = SystemTime::now();
             //If party received messages from leaders, we let it forward dealer's message to other verifiers 
-            leader_messages_to_forward_to_verifiers_from_verifier = verifier.receive_from_leaders(messages_to_send_to_verifiers_from_leaders.get(&i).unwrap());
-            comm_overall += (4.0*(leader_messages_to_forward_to_verifiers_from_verifier.len() as f64) 
-            *(leader_messages_to_forward_to_verifiers_from_verifier.values().last().unwrap().len()as f64))/1000000.0;
-
-            for future_verifier in i..=3*t+1 {
-                let messages_to_this_verifier_as_participant = leader_messages_to_forward_to_verifiers_from_verifier.get(&future_verifier).unwrap();
-                messages_to_send_to_verifiers_from_verifiers.get_mut(&future_verifier)
-                                                            .unwrap()
-                                                            .insert(i, messages_to_this_verifier_as_participant.clone());
+            let mut leader_messages_to_forward_to_verifiers_from_verifier = verifier.receive_from_leaders(&messages_to_send_to_verifiers_from_leaders.remove(&i).unwrap_or_default());
+            let mut verifier_time = SystemTime::now().duration_since(verifier_start_time).unwrap();
+            if adversary.is_corrupted(i as u64) {
+                adversary.tamper_forwarded_values(i as u64, &mut leader_messages_to_forward_to_verifiers_from_verifier);
+            }
+            if let Some(transcript) = transcript.as_deref_mut() {
+                record_values(transcript, MessageKind::ForwardedValues, i, &leader_messages_to_forward_to_verifiers_from_verifier)?;
             }
 
+            for future_verifier in i..=n {
+                let messages_to_this_verifier_as_participant = leader_messages_to_forward_to_verifiers_from_verifier.get(&future_verifier).cloned().unwrap_or_default();
+                messages_to_send_to_verifiers_from_verifiers.entry(future_verifier)
+                                                            .or_default()
+                                                            .insert(i, messages_to_this_verifier_as_participant);
+            }
+
+            let verifier_start_time = SystemTime::now();
             //Verification phase: receive messages from prior verifiers
-            verifier.receive_from_parties(messages_to_send_to_verifiers_from_verifiers.get(&i).unwrap());
+            verifier.receive_from_parties(&messages_to_send_to_verifiers_from_verifiers.remove(&i).unwrap_or_default());
             //Verification phase: finalize processing of all messages received
-            let messages_to_send_to_publishers_as_participant = verifier.process_all_participation_subsets();
-            comm_overall += (4.0*(messages_to_send_to_publishers_as_participant.len() as f64) *
-            (messages_to_send_to_publishers_as_participant.values().last().unwrap().len()as f64))/1000000.0;
-
-            for publisher in 1..=3*t+1 {
-                let messages_to_this_publisher_from_this_verifier = messages_to_send_to_publishers_as_participant.get(&publisher).unwrap();
-                messages_to_send_to_publishers.get_mut(&publisher)
-                                                .unwrap()
-                                                .insert(i, messages_to_this_publisher_from_this_verifier.clone());
+            let mut messages_to_send_to_publishers_as_participant = verifier.process_all_participation_subsets();
+            verifier_time += SystemTime::now().duration_since(verifier_start_time).unwrap();
+            if adversary.is_corrupted(i as u64) {
+                adversary.tamper_verified_values(i as u64, &mut messages_to_send_to_publishers_as_participant);
+            }
+            if let Some(transcript) = transcript.as_deref_mut() {
+                record_values(transcript, MessageKind::VerifiedValues, i, &messages_to_send_to_publishers_as_participant)?;
+            }
+            complaints.insert(i, verifier.complaints);
+            faulty.insert(i, verifier.faulty);
+
+            let (forwarded_messages, forwarded_bytes) = point_to_point_cost(i, &leader_messages_to_forward_to_verifiers_from_verifier);
+            let (published_messages, published_bytes) = point_to_point_cost(i, &messages_to_send_to_publishers_as_participant);
+            report.record(i as u64, Role::Verifier, 0, 0, verifier_time);
+            report.record_messages(i as u64, Role::Verifier, MessageKind::ForwardedValues, forwarded_messages, forwarded_bytes);
+            report.record_messages(i as u64, Role::Verifier, MessageKind::VerifiedValues, published_messages, published_bytes);
+
+            for publisher in 1..=n {
+                let messages_to_this_publisher_from_this_verifier = messages_to_send_to_publishers_as_participant.get(&publisher).cloned().unwrap_or_default();
+                messages_to_send_to_publishers.entry(publisher)
+                                                .or_default()
+                                                .insert(i, messages_to_this_publisher_from_this_verifier);
             }
         }
 
-        for i in 1..=3*t+1 {
-            let mut publisher = Publisher {t: t, 
-                                                    index: i, 
-                                                    all_subsets: Vec::new(), 
-                                                    my_subsets: BTreeMap::new(), 
-                                                    received_values_of_participation_subsets : BTreeMap::new() };
-            publisher.init();
-            let messages_to_this_publisher = messages_to_send_to_publishers.get(&i).unwrap();
-            let publisher_messages = publisher.process(messages_to_this_publisher);
-            comm_overall += (4.0*(messages_send_by_publishers.values().last().unwrap().len() as f64))/1000000.0;
+        for i in 1..=n {
+            let mut publisher = Publisher {params: params, index: i};
+            let messages_to_this_publisher = messages_to_send_to_publishers.remove(&i).unwrap_or_default();
+            let publisher_start_time = SystemTime::now();
+            let mut publisher_messages = publisher.process(&messages_to_this_publisher);
+            let publisher_time = SystemTime::now().duration_since(publisher_start_time).unwrap();
+            if adversary.is_corrupted(i as u64) {
+                adversary.tamper_published(i as u64, &mut publisher_messages);
+            }
+            if let Some(transcript) = transcript.as_deref_mut() {
+                transcript.record(MessageKind::PublishedValues, i as u64, None, encode_values(&publisher_messages)?);
+            }
+            //Publishers broadcast their results as a single message
+            report.record(i as u64, Role::Publisher, 0, 0, publisher_time);
+            report.record_messages(i as u64, Role::Publisher, MessageKind::PublishedValues, 1, ENTRY_BYTES * publisher_messages.len() as u64);
 
             messages_send_by_publishers.insert(i, publisher_messages);
         }
 
-        let client = Client{received_values_of_participation_subsets: messages_send_by_publishers };
+        let mut client = Client{received_values_of_participation_subsets: messages_send_by_publishers };
+        let coin = client.compute_coin();
 
         let end_time = SystemTime::now();
         let duration = end_time.duration_since(start_time).unwrap();
-        println!("Whole protocol takes {} milliseconds", duration.as_millis());
-        println!("Whole protocol has comm {}", comm_overall);
 
+        Ok(ExtractorOutcome {
+            coin: coin,
+            time: duration,
+            report: report,
+            complaints: complaints,
+            published: client.received_values_of_participation_subsets,
+            faulty: faulty,
+        })
     }
 }
 
-impl Publisher {
-    pub fn init(&mut self) {
-        let start_time = SystemTime::now();
-        let mut all_subsets: Vec<Vec<usize>> = Vec::new();
-        let mut current_subset: Vec<usize> = Vec::new();
-        //let nums = (1..=3 * t + 1).collect::<Vec<_>>();
-        let max_num = 3*self.t + 1;
-        let subset_size = 2*self.t as usize + 1;
-
-        generate_subsets(max_num, subset_size, 1, &mut current_subset, &mut all_subsets);
-        let mut my_subsets: BTreeMap<usize,Vec<usize>> = Default::default();
-
-        let number_subsets = all_subsets.len();
-
-        for subset_index in 0..number_subsets {
-            if (all_subsets[subset_index].contains(&self.index)) {
-                my_subsets.insert(subset_index, all_subsets[subset_index].clone());
+impl ExtractorParams {
+    ///One-line description of the family and how much of it survives t corruptions
+    pub fn describe(&self) -> String {
+        match &self.family {
+            SubsetFamily::Complete => format!("{} subsets of size {} out of {} parties, at least {} fully honest with {} corruptions",
+                    self.number_subsets(), self.k, self.n, self.guaranteed_honest_subsets().unwrap(), self.t),
+            SubsetFamily::Explicit(subsets) => {
+                let analysis = analyze(subsets, self.n, self.t, ANALYSIS_SAMPLES, 0);
+                let how = if analysis.exact { "over all corruption sets".to_string() } else { format!("estimated from {} sampled corruption sets", ANALYSIS_SAMPLES) };
+                format!("{} subsets of size {} out of {} parties, {} random corruptions control all of them with probability {} ({})",
+                    analysis.number_subsets, self.k, self.n, self.t, analysis.probability_adversary_controls_all, how)
             }
         }
+    }
+}
 
-        self.all_subsets = all_subsets;
-        self.my_subsets = my_subsets;
+impl ExtractorOutcome {
+    pub fn print(&self) {
+        println!("Whole protocol takes {} milliseconds", self.time.as_millis());
+        println!("Whole protocol has comm {}", (self.report.total().bytes as f64)/1000000.0);
+        self.report.print_per_party();
+        self.report.print_by_kind();
+        println!("Coin: {}", self.coin);
+    }
+}
 
+fn encode_values(values: &BTreeMap<usize, i32>) -> Result<Vec<u8>> {
+    bincode::serialize(values).map_err(|error| Error::Transcript(error.to_string()))
+}
 
-        let end_time = SystemTime::now();
-        let duration = end_time.duration_since(start_time).unwrap();
-        //println!("Publisher init takes {} milliseconds", duration.as_millis());
+fn decode_values(bytes: &[u8]) -> Option<BTreeMap<usize, i32>> {
+    bincode::deserialize(bytes).ok()
+}
 
+//Records a batch of point-to-point messages; empty messages are never sent
+fn record_values(transcript: &mut Transcript, kind: MessageKind, sender: usize, messages: &BTreeMap<usize, BTreeMap<usize, i32>>) -> Result<()> {
+    for (recipient, values) in messages.iter().filter(|(_, values)| !values.is_empty()) {
+        transcript.record(kind, sender as u64, Some(*recipient as u64), encode_values(values)?);
     }
+    Ok(())
+}
 
-    pub fn process(&mut self, random_values_from_verifiers: &BTreeMap<usize, BTreeMap<usize, i32>>) -> BTreeMap<usize, i32>{
+impl RandomnessExtractor {
+    ///Replays a transcript of execute_recorded offline: checks that every leader sent one bit per led subset to all its members,
+    ///re-runs every verifier and publisher on what was recorded as sent to it, flags each message that differs from what
+    ///the honest role sends and recomputes the coin from what the publishers published
+    pub fn verify_transcript(transcript: &Transcript) -> Result<Verification<i32>> {
+        let params = match &transcript.protocol {
+            Protocol::Extractor { n, k, t, leader_rule, subsets } => ExtractorParams {
+                n: *n,
+                k: *k,
+                t: *t,
+                leader_rule: *leader_rule,
+                family: subsets.clone().map_or(SubsetFamily::Complete, SubsetFamily::explicit),
+            },
+            _ => return Err(Error::Transcript("not an extractor transcript".to_string())),
+        };
+        params.validate()?;
+        let n = params.n;
+        let claimed = transcript.output.as_slice().try_into().map(i32::from_le_bytes)
+                                .map_err(|_| Error::Transcript("the claimed coin is not an i32".to_string()))?;
+        //Messages that were never recorded are empty
+        let values = |kind: MessageKind, sender: usize, receiver: usize| -> BTreeMap<usize, i32> {
+            transcript.message(0, kind, sender as u64, Some(receiver as u64)).and_then(decode_values).unwrap_or_default()
+        };
+        let mut deviations = Vec::new();
+        let mut deviate = |kind: MessageKind, party: usize, reason: String| deviations.push(Deviation::new(0, kind, party as u64, reason));
+        let new_verifier = |index: usize| Verifier {params: &params,
+                                                index: index,
+                                                seed: None,
+                                                values_of_participation_subsets: BTreeMap::new(),
+                                                agreeable_parties_for_participation_subsets: BTreeMap::new(),
+                                                complaints: 0,
+                                                faulty: BTreeSet::new()};
+
+        //Leaders: only bits for subsets they lead, to members, and the same bit to every member
+        for leader in 1..=n {
+            for member in 1..=n {
+                let verifier = new_verifier(member);
+                for (subset_index, value) in values(MessageKind::LeaderValues, leader, member) {
+                    if let Err(error) = verifier.check_leader_value(leader, subset_index, value) {
+                        deviate(MessageKind::LeaderValues, leader, error.to_string());
+                    }
+                }
+            }
+            for (subset_index, subset) in params.leader_subsets(leader) {
+                let sent: BTreeSet<Option<i32>> = subset.iter().map(|member| values(MessageKind::LeaderValues, leader, *member).get(&subset_index).copied()).collect();
+                if sent.len() != 1 || sent.contains(&None) {
+                    deviate(MessageKind::LeaderValues, leader, format!("did not send every member of subset {} the same value", subset_index));
+                }
+            }
+        }
 
-        let start_time = SystemTime::now();
-        let mut subset_results: BTreeMap<usize, i32> = BTreeMap::new();
-
-        for subset_index in self.my_subsets.keys() {
-            let mut number_votes_for_zero = 0;
-            let mut number_votes_for_one = 0;
-            for verifier in self.my_subsets.get(subset_index).unwrap() {
-                if random_values_from_verifiers.get(verifier).unwrap().get(subset_index) == Some(&1) {
-                    number_votes_for_one += 1;
-                } else {
-                    number_votes_for_zero += 0;
+        let mut published: BTreeMap<usize, BTreeMap<usize, i32>> = BTreeMap::new();
+        for i in 1..=n {
+            let mut verifier = new_verifier(i);
+            let from_leaders: BTreeMap<usize, BTreeMap<usize, i32>> = (1..=n).map(|leader| (leader, values(MessageKind::LeaderValues, leader, i))).collect();
+            let forwarded = verifier.receive_from_leaders(&from_leaders);
+            for later in i..=n {
+                if values(MessageKind::ForwardedValues, i, later) != forwarded.get(&later).cloned().unwrap_or_default() {
+                    deviate(MessageKind::ForwardedValues, i, format!("forwarded to verifier {} something else than the leaders sent", later));
+                }
+            }
+
+            let from_parties: BTreeMap<usize, BTreeMap<usize, i32>> = (1..=i).map(|party| (party, values(MessageKind::ForwardedValues, party, i))).collect();
+            verifier.receive_from_parties(&from_parties);
+            let verified = verifier.process_all_participation_subsets();
+            for publisher in 1..=n {
+                if values(MessageKind::VerifiedValues, i, publisher) != verified.get(&publisher).cloned().unwrap_or_default() {
+                    deviate(MessageKind::VerifiedValues, i, format!("sent publisher {} values it did not verify", publisher));
                 }
             }
-            if number_votes_for_one > number_votes_for_zero {
-                subset_results.insert(*subset_index, 1);
-                //publish this output
-                //println!("One won for {}", *subset_index)
-            }  else {
-                subset_results.insert(*subset_index, 0);
-                //println!("Zero won for {}", *subset_index)
+        }
+
+        for i in 1..=n {
+            let mut publisher = Publisher {params: &params, index: i};
+            let from_verifiers: BTreeMap<usize, BTreeMap<usize, i32>> = (1..=n).map(|verifier| (verifier, values(MessageKind::VerifiedValues, verifier, i))).collect();
+            let expected = publisher.process(&from_verifiers);
+            match transcript.message(0, MessageKind::PublishedValues, i as u64, None).and_then(decode_values) {
+                Some(values) => {
+                    if values != expected {
+                        deviate(MessageKind::PublishedValues, i, "published results that differ from the verified values".to_string());
+                    }
+                    published.insert(i, values);
+                },
+                None => deviate(MessageKind::PublishedValues, i, "published nothing".to_string()),
             }
         }
 
-        let end_time = SystemTime::now();
-        let duration = end_time.duration_since(start_time).unwrap();
+        //The client reads whatever was published
+        let mut client = Client { received_values_of_participation_subsets: published };
+        Ok(Verification { output: client.compute_coin(), claimed: claimed, deviations: deviations })
+    }
+}
+
+//Bytes per transmitted subset value: the subset index (8 bytes) and the value itself (4 bytes)
+const ENTRY_BYTES: u64 = 12;
+
+//Messages and bytes of a batch of point-to-point messages; empty messages and messages to oneself are free
+fn point_to_point_cost(sender: usize, messages: &BTreeMap<usize, BTreeMap<usize, i32>>) -> (u64, u64) {
+    messages.iter()
+        .filter(|(recipient, values)| **recipient != sender && !values.is_empty())
+        .fold((0, 0), |(count, bytes), (_, values)| (count + 1, bytes + ENTRY_BYTES * values.len() as u64))
+}
+
+impl Publisher<'_> {
+    //Subsets are enumerated lazily, so a publisher only ever holds the subsets it is responsible for
+    pub fn my_subsets(&self) -> Box<dyn Iterator<Item = (usize, Vec<usize>)> + Send> {
+        self.params.participation_subsets(self.index)
+    }
+
+    pub fn process(&mut self, random_values_from_verifiers: &BTreeMap<usize, BTreeMap<usize, i32>>) -> BTreeMap<usize, i32>{
+
+        //Each subset is tallied independently, so this runs in parallel if enabled
+        let subset_results: BTreeMap<usize, i32> = cfg_par_bridge!(self.my_subsets())
+            .map(|(subset_index, subset)| {
+                //Verifiers that sent nothing for the subset count as votes for zero
+                let votes_for_one = subset.iter()
+                    .filter(|verifier| random_values_from_verifiers.get(verifier).and_then(|values| values.get(&subset_index)) == Some(&1))
+                    .count();
+                (subset_index, majority_bit(votes_for_one, subset.len()))
+            })
+            .collect();
 
         subset_results
-        //println!("Publsiher processing takes {} milliseconds", duration.as_millis());
     }
 }
 
-impl Client {
-    pub fn compute_coin(&mut self) {
-        let coin = 0;
+//Bit a strict majority of a subset's members voted for: one only if more members voted one than zero,
+//so the at most t corrupted members of a subset with an honest majority cannot flip its bit
+fn majority_bit(votes_for_one: usize, members: usize) -> i32 {
+    if 2*votes_for_one > members { 1 } else { 0 }
+}
 
+impl Client {
+    ///XOR of the subset bits, where a subset's bit is one if more publishers reported one than zero for it.
+    ///A single fully honest subset contributes an unbiased bit, so the parity is unbiased as well.
+    pub fn compute_coin(&mut self) -> i32 {
         let mut count_maj_for_subsets: BTreeMap<usize, i32> = BTreeMap::new();
 
-        for publisher in self.received_values_of_participation_subsets.keys() {
-            for subset in self.received_values_of_participation_subsets.get(publisher).unwrap().keys() {
-                if self.received_values_of_participation_subsets.get(publisher).unwrap().get(subset) == Some(&1) {
-                    count_maj_for_subsets.insert(*subset, count_maj_for_subsets[subset] + 1);
+        for published in self.received_values_of_participation_subsets.values() {
+            for (subset, value) in published {
+                if *value == 1 {
+                    *count_maj_for_subsets.entry(*subset).or_insert(0) += 1;
                 } else {
-                    count_maj_for_subsets.insert(*subset, count_maj_for_subsets[subset] - 1);
+                    *count_maj_for_subsets.entry(*subset).or_insert(0) -= 1;
                 }
             }
         }
 
-        for subset in count_maj_for_subsets.keys() {
-            if count_maj_for_subsets[subset] > 0 {
-                //Ones won
-                coin != coin; 
-            }
-        }
+        //Ones won the subsets with a positive count; each of them flips the coin
+        count_maj_for_subsets.values().filter(|majority| **majority > 0).fold(0, |coin, _| coin ^ 1)
     }
 
 }
 
-impl Verifier {
-    pub fn init(&mut self) {
-
-        let start_time = SystemTime::now();
-
-        let mut all_subsets: Vec<Vec<usize>> = Vec::new();
-        let mut current_subset: Vec<usize> = Vec::new();
-        //let nums = (1..=3 * t + 1).collect::<Vec<_>>();
-        let max_num = 3*self.t + 1;
-        let subset_size = 2*self.t as usize + 1;
-
-        generate_subsets(max_num, subset_size, 1, &mut current_subset, &mut all_subsets);
-
-        let mut my_leader_subsets: BTreeMap<usize,Vec<usize>> = Default::default();
-        let number_subsets = all_subsets.len();
+//Subset values are single bits
+fn is_bit(value: i32) -> bool {
+    value == 0 || value == 1
+}
 
-        for subset_index in 0..number_subsets {
-            if all_subsets[subset_index][0] == self.index {
-                my_leader_subsets.insert(subset_index, all_subsets[subset_index].clone());
-            }
+impl Verifier<'_> {
+    //Checks that `leader` leads the subset, that we are a member and that the value is a bit; returns the subset
+    fn check_leader_value(&self, leader: usize, subset_index: usize, value: i32) -> Result<Vec<usize>> {
+        let malformed = |reason: String| Error::MalformedMessage { from: leader as u64, reason: reason };
+        if subset_index >= self.params.number_subsets() {
+            return Err(malformed(format!("subset {} does not exist", subset_index)));
         }
-        let mut my_participation_subsets: BTreeMap<usize,Vec<usize>> = Default::default();
-
-        for subset_index in 0..number_subsets {
-            if all_subsets[subset_index].contains(&self.index) {
-                my_participation_subsets.insert(subset_index, all_subsets[subset_index].clone());
-            }
+        let subset = self.params.subset(subset_index);
+        if !subset.contains(&self.index) || self.params.leader_of(subset_index, &subset) != leader {
+            return Err(malformed(format!("not the leader of subset {} or party {} is no member", subset_index, self.index)));
         }
+        if !is_bit(value) {
+            return Err(malformed(format!("value {} for subset {} is not a bit", value, subset_index)));
+        }
+        Ok(subset)
+    }
 
-        self.all_subsets = all_subsets;
-        self.my_leader_subsets = my_leader_subsets;
-        self.my_participation_subsets = my_participation_subsets;
-
-        let end_time = SystemTime::now();
-        let duration = end_time.duration_since(start_time).unwrap();
-        //println!("Verifier init takes {} milliseconds", duration.as_millis());
+    //Subsets this party leads, enumerated lazily instead of filtering the whole family
+    pub fn my_leader_subsets(&self) -> Box<dyn Iterator<Item = (usize, Vec<usize>)> + Send> {
+        self.params.leader_subsets(self.index)
     }
 
+    //Subsets this party participates in, enumerated lazily
+    pub fn my_participation_subsets(&self) -> Box<dyn Iterator<Item = (usize, Vec<usize>)> + Send> {
+        self.params.participation_subsets(self.index)
+    }
 
     //Return a map of <verifier_to_send_msg_to, <subset_index, random_value>>
     pub fn lead(&self) -> BTreeMap<usize, BTreeMap<usize, i32>> {
-
-        let start_time = SystemTime::now();
-
-        let mut rng = rand::thread_rng();
         let mut messages_to_send_to_verifiers: BTreeMap<usize, BTreeMap<usize, i32>> = Default::default();
 
-        for verifier in 1..=3*self.t + 1 {
+        for verifier in 1..=self.params.n {
             messages_to_send_to_verifiers.insert(verifier, BTreeMap::new());
         }
 
-        for (subset_index,subset) in &self.my_leader_subsets {
-            let random_value = rng.gen_bool(0.5) as i32;
-
+        //Sample the random values of all led subsets (in parallel if enabled), then distribute them
+        let led_subsets: Vec<(usize, Vec<usize>, i32)> = cfg_par_bridge!(self.my_leader_subsets())
+            .map(|(subset_index, subset)| {
+                let random_value = match self.seed {
+                    //Each subset gets its own stream so that the values do not depend on the order of evaluation
+                    Some(seed) => StdRng::seed_from_u64(seed ^ (subset_index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)).gen_bool(0.5),
+                    None => rand::thread_rng().gen_bool(0.5),
+                };
+                (subset_index, subset, random_value as i32)
+            })
+            .collect();
+
+        for (subset_index, subset, random_value) in led_subsets {
             for verifier in subset {
-                let messages_to_sent_to_verifier = messages_to_send_to_verifiers.get_mut(&verifier).unwrap();
-                messages_to_sent_to_verifier.insert(*subset_index, random_value);
+                let messages_to_sent_to_verifier = messages_to_send_to_verifiers.entry(verifier).or_default();
+                messages_to_sent_to_verifier.insert(subset_index, random_value);
             }
         }
 
-        let end_time = SystemTime::now();
-        let duration = end_time.duration_since(start_time).unwrap();
-        //println!("Verifier lead takes {} milliseconds", duration.as_millis());
-
         messages_to_send_to_verifiers
 
     }
     pub fn receive_from_leaders(&mut self, random_values_from_dealers: &BTreeMap<usize,BTreeMap<usize,i32>>) -> BTreeMap<usize, BTreeMap<usize, i32>> {
-        let start_time = SystemTime::now();
-
         let mut messages_to_send_to_verifiers: BTreeMap<usize, BTreeMap<usize, i32>> = Default::default();
-        for verifier in self.index..=3*self.t + 1 {
+        for verifier in self.index..=self.params.n {
             messages_to_send_to_verifiers.insert(verifier, BTreeMap::new());
         }
 
-        //We will go through all dealers that were executed before this party
-        let mut max_dealer = self.t + 1;
-        if (self.index < max_dealer) {
-            max_dealer = self.index;
-        }
-
-        for current_leader in 1..=max_dealer {
-            let current_dealer_subsets = random_values_from_dealers.get(&current_leader).unwrap().keys();
-            
-            for subset_index in  current_dealer_subsets{
-                let random_value = random_values_from_dealers[&current_leader][&subset_index];
+        //We will go through all leaders that sent us something
+        for (current_leader, current_leader_values) in random_values_from_dealers {
+            for (subset_index, random_value) in current_leader_values {
+                //Only accept values from the actual leader of a subset we are a member of
+                let subset = match self.check_leader_value(*current_leader, *subset_index, *random_value) {
+                    Ok(subset) => subset,
+                    //The leader is marked faulty; its value is ignored
+                    Err(_) => {
+                        self.faulty.insert(*current_leader);
+                        continue;
+                    }
+                };
+                let random_value = *random_value;
                 //Store x^j_S as the set value received by the corresponding dealer
                 self.values_of_participation_subsets.insert(*subset_index, random_value);
 
                 //Send x^j_S to all verifiers in the corresponding subset down the line
-                for verifier in self.my_participation_subsets.get(subset_index).unwrap() {
-                    if *verifier > self.index - 1 {
-                        let messages_to_send_to_verifier = messages_to_send_to_verifiers.get_mut(&verifier).unwrap();
+                for verifier in subset {
+                    if verifier > self.index - 1 {
+                        let messages_to_send_to_verifier = messages_to_send_to_verifiers.entry(verifier).or_default();
                         messages_to_send_to_verifier.insert(*subset_index, random_value);
                     } 
                 }
             }
         }
-        let end_time = SystemTime::now();
-        let duration = end_time.duration_since(start_time).unwrap();
-        //println!("Verifier receive from leaders takes {} milliseconds", duration.as_millis());
         messages_to_send_to_verifiers
     }
 
     pub fn receive_from_parties(&mut self, random_values_from_prior_parties: &BTreeMap<usize, BTreeMap<usize,i32>>) {
-        let start_time = SystemTime::now();
-
-        for (subset_index, subset) in &self.my_participation_subsets {
-            self.agreeable_parties_for_participation_subsets.insert(*subset_index, Default::default());
-        }
-
         //Go through each prior party and messages we received from that party
         for current_party in 1..=self.index {
-            for (subset_index, random_value) in random_values_from_prior_parties.get(&current_party).unwrap() {
+            for (subset_index, random_value) in random_values_from_prior_parties.get(&current_party).into_iter().flatten() {
+                if !is_bit(*random_value) {
+                    self.faulty.insert(current_party);
+                    continue;
+                }
                 //If the subset value we received from the party is not consistent with what the leader of that subset sent us, we coomplain
-                if self.values_of_participation_subsets.get(&subset_index) != Some(random_value) {
-                    //complain here
+                if self.values_of_participation_subsets.get(subset_index) != Some(random_value) {
+                    self.complaints += 1;
                 } else {
                     //If the values are consistent, we add the party to the set of party which agree for that particular subset
-                    let agreable_parties_for_subset =  self.agreeable_parties_for_participation_subsets.get_mut(subset_index).unwrap();
+                    let agreable_parties_for_subset =  self.agreeable_parties_for_participation_subsets.entry(*subset_index).or_default();
                     agreable_parties_for_subset.push(current_party);
                 }
             }
         }
-
-        let end_time = SystemTime::now();
-        let duration = end_time.duration_since(start_time).unwrap();
-        //println!("Verification msg processing takes {} milliseconds", duration.as_millis());
     }
 
     pub fn process_all_participation_subsets(&mut self) -> BTreeMap<usize, BTreeMap<usize, i32>> {
-        let start_time = SystemTime::now();
-
         let mut messages_to_send_to_publishers: BTreeMap<usize, BTreeMap<usize, i32>> = Default::default();
-        for publisher in 1..=3*self.t + 1 {
+        for publisher in 1..=self.params.n {
             let subset_map: BTreeMap<usize, i32> = BTreeMap::new();
             messages_to_send_to_publishers.insert(publisher, subset_map);
         }
-        //Go through all subsets in which I participated
-        for (subset_index, subset) in &self.my_participation_subsets {
-            let mut subset_not_complete = false;
-            //Check if all parties which were supposed to agree actually agreed
-            for party in subset {
-                if (*party < self.index) && !self.agreeable_parties_for_participation_subsets
+        //Go through all subsets in which I participated (in parallel if enabled) and keep the complete ones
+        let complete_subsets: Vec<(usize, Vec<usize>, i32)> = cfg_par_bridge!(self.my_participation_subsets())
+            .filter_map(|(subset_index, subset)| {
+                //Check that the leader's value arrived and that all parties which were supposed to agree actually agreed
+                let value = *self.values_of_participation_subsets.get(&subset_index)?;
+                let agreed = subset.iter().all(|party| *party >= self.index || self.agreeable_parties_for_participation_subsets
                                                                 .get(&subset_index)
-                                                                .unwrap()
-                                                                .contains(&party) {
-                    subset_not_complete = true;
-                    break;
-                }
-            }
-            //If we received all the values that we anticipated, we proceed by including these messages into the list that we will send to publishers
-            if !subset_not_complete {
-                for publisher in subset {
-                    messages_to_send_to_publishers.get_mut(&publisher).unwrap().insert(*subset_index, 
-                        *self.values_of_participation_subsets.get(&subset_index).unwrap());                        
-                }
+                                                                .is_some_and(|parties| parties.contains(party)));
+                if agreed { Some((subset_index, subset, value)) } else { None }
+            })
+            .collect();
+
+        //If we received all the values that we anticipated, we proceed by including these messages into the list that we will send to publishers
+        for (subset_index, subset, value) in complete_subsets {
+            for publisher in &subset {
+                messages_to_send_to_publishers.entry(*publisher).or_default().insert(subset_index, value);
             }
         }
 
-        let end_time = SystemTime::now();
-        let duration = end_time.duration_since(start_time).unwrap();
-        //println!("Verification msg generation takes {} milliseconds", duration.as_millis());
         messages_to_send_to_publishers
     }
 }
 
-pub fn generate_subsets(max_num: usize, subset_size: usize, start_idx: usize, current_subset: &mut Vec<usize>, all_subsets: &mut Vec<Vec<usize>>) {
-    if current_subset.len() == subset_size {
-        all_subsets.push(current_subset.clone());
-        return;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_subset_takes_the_bit_of_a_strict_majority() {
+        assert_eq!(majority_bit(1, 3), 0);
+        assert_eq!(majority_bit(2, 3), 1);
+        assert_eq!(majority_bit(2, 4), 0);
+        assert_eq!(majority_bit(0, 3), 0);
     }
 
-    for i in start_idx..=max_num {
-        current_subset.push(i);
-        generate_subsets(max_num, subset_size, i + 1, current_subset, all_subsets);
-        current_subset.pop();
+    #[test]
+    fn the_coin_is_the_parity_of_the_subsets_ones_won() {
+        //Publishers 1 to 3 report subsets 0 to 2; ones win subsets 0 and 1, zeros win subset 2
+        let reports = [(0, [1, 1, 0]), (1, [1, 1, 1]), (2, [0, 1, 0])];
+        let mut published: BTreeMap<usize, BTreeMap<usize, i32>> = BTreeMap::new();
+        for (subset, values) in reports {
+            for (publisher, value) in values.into_iter().enumerate() {
+                published.entry(publisher + 1).or_default().insert(subset, value);
+            }
+        }
+        assert_eq!(Client { received_values_of_participation_subsets: published.clone() }.compute_coin(), 0);
+
+        published.get_mut(&3).unwrap().insert(2, 1);
+        published.get_mut(&1).unwrap().insert(2, 1);
+        assert_eq!(Client { received_values_of_participation_subsets: published }.compute_coin(), 1);
     }
 }
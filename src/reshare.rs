@@ -0,0 +1,316 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, SystemTime};
+
+use ark_ff::Field;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::adversary::{Adversary, DealingView};
+use crate::error::{Error, Result};
+use crate::metrics::{CostReport, MessageKind, Metrics, Role, RoleCost};
+use crate::polynomials::Poly;
+use crate::vss::{display_values, points_of, PubParams, Resolver, Sharing, VSS};
+
+//Resharing passes the secrets of a dealing on to a fresh committee without opening them. For every packed slot k
+//receiver i of the old committee holds P_k(x_i), where P_k has the degree of the sharing and takes the k-th secret
+//at the k-th secret point; after a dealing P_k is the column of the bivariate polynomial at that point.
+//Each holder deals its values to the new committee like any dealer would, and the secrets are fixed linear
+//combinations of the dealt values. New receiver j applies the same combination to the rows it got
+//and holds values of the same kind, so the secrets can be passed on again in the next epoch.
+//The VSS only shows that each dealing is consistent, not that a holder dealt the values its row gives.
+//The dealt values of the qualified holders should lie on P_k, so before recombining the new receivers open the syndromes,
+//the combinations of dealt values that vanish on every polynomial of the sharing's degree.
+//They are zero for honest holders and reveal nothing about P_k; decoding them points at the holders that dealt other values,
+//and the secrets are recombined from all other qualified holders.
+
+///Shares of packed secrets held by the receivers of a committee
+pub struct Holding<F: Field> {
+    //Evaluation points of the committee's receivers
+    points: Vec<F>,
+    //One value per packed slot, for every receiver holding a valid share
+    pub shares: BTreeMap<u64, Vec<F>>,
+}
+
+///One epoch: the dealings of the old committee's holders to the new committee
+pub struct Handover<F: Field> {
+    //Holders whose dealing qualified; the secrets are recombined from all of them but the misdealt ones
+    pub qualified: Vec<u64>,
+    pub complaints: BTreeMap<u64, u64>,
+    //Qualified holders whose dealt values are off the polynomial the old committee shares
+    pub misdealt: BTreeSet<u64>,
+    //Costs of each dealing, indexed by holder
+    pub dealings: BTreeMap<u64, CostReport>,
+    //Costs of the new receivers opening the syndromes
+    pub checking: CostReport,
+    //What the new committee holds
+    pub holding: Holding<F>,
+    //The recombined dealings, each with its weight per slot
+    combined: Vec<(Vec<F>, VSS<F>, Sharing<F>)>,
+}
+
+///Deals secrets to a first committee and passes them on through `epochs` further committees before opening them
+pub struct Resharing<F: Field> {
+    //Dealing to the first committee; its seed also seeds the resharings
+    pub dealing: VSS<F>,
+    pub epochs: u64,
+}
+
+///Costs of every epoch and the secrets the last committee opened
+pub struct ResharingOutcome<F: Field> {
+    pub secrets: Vec<F>,
+    //Whether the last committee's dealings could be reconstructed
+    pub reconstructed: bool,
+    //Whether the opened secrets are the ones first dealt
+    pub success: bool,
+    //Costs of the first dealing
+    pub dealing: CostReport,
+    pub handovers: Vec<Handover<F>>,
+    //Costs of opening the secrets with the last committee
+    pub opening: CostReport,
+    pub time: Duration,
+}
+
+//Polynomial through the values of a row at the first degree+1 points, evaluated at `at`
+fn evaluate_row<F: Field>(points: &[F], row: &[F], degree: u64, at: &[F]) -> Result<Vec<F>> {
+    let needed = (degree + 1) as usize;
+    if row.len() < needed {
+        return Err(Error::NotEnoughShares { needed: degree + 1, verified: row.len() as u64 });
+    }
+    let unipoly: Poly<F> = Poly::evals_to_coeffs(&points[..needed], &row[..needed])?;
+    Ok(at.iter().map(|point| unipoly.eval(*point)).collect())
+}
+
+//Weights c_i with p(at) = sum of c_i·p(xs[i]) for every polynomial p of degree below xs.len()
+pub(crate) fn lagrange_weights<F: Field>(xs: &[F], at: F) -> Vec<F> {
+    xs.iter().enumerate()
+        .map(|(i, x_i)| {
+            xs.iter().enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(F::ONE, |weight, (_, x_j)| weight * (at - x_j) / (*x_i - x_j))
+        })
+        .collect()
+}
+
+impl<F: Field> Holding<F> {
+    ///What the receivers of a dealing under `pp` hold: their rows at the secret points
+    pub fn of(sharing: &Sharing<F>, pp: &PubParams) -> Result<Holding<F>> {
+        let (points, secret_points) = points_of::<F>(&sharing.pki[1..], pp.packing)?;
+        let shares = sharing.rows.iter()
+            .map(|(i, row)| Ok((*i, evaluate_row(&points, row, pp.degree(), &secret_points)?)))
+            .collect::<Result<BTreeMap<u64, Vec<F>>>>()?;
+        Ok(Holding { points: points, shares: shares })
+    }
+}
+
+//Holders among `qualified` whose dealt values do not lie on a polynomial of `degree` over their points.
+//`held` has, for every new receiver that kept a row from each qualified dealing, its values at the secret points by holder.
+//With B the first degree+1 holders, the syndrome of holder m is v_m minus the value at x_m of the polynomial through v on B.
+//If the dealt values are P(x) + e, it is e_m - E(x_m) with E the polynomial through e on B, and zero on B itself,
+//so decoding the syndromes as a word of `degree` leaves the codeword -E and finds the errors where e is not zero.
+//Every new receiver publishes its shares of the syndromes, which are opened at the secret points with error correction.
+fn misdealt_holders<F: Field, M: Metrics>(qualified: &[u64], holder_points: &[F], held: &BTreeMap<u64, BTreeMap<u64, Vec<F>>>, degree: u64,
+                                          next_points: &[F], secret_points: &[F], next_degree: u64, metrics: &mut M) -> Result<BTreeSet<u64>> {
+    let base = &qualified[..(degree + 1) as usize];
+    let base_points: Vec<F> = base.iter().map(|holder| holder_points[(holder - 1) as usize]).collect();
+    let checked: Vec<(u64, Vec<F>)> = qualified[base.len()..].iter()
+        .map(|holder| (*holder, lagrange_weights(&base_points, holder_points[(holder - 1) as usize])))
+        .collect();
+
+    let receivers: Vec<u64> = held.keys().cloned().collect();
+    let receiver_points: Vec<F> = receivers.iter().map(|j| next_points[(j - 1) as usize]).collect();
+    for j in &receivers {
+        let bytes = (checked.len() * secret_points.len() * F::ZERO.uncompressed_size()) as u64;
+        metrics.record_messages(*j, Role::Receiver, MessageKind::Syndromes, 1, bytes);
+    }
+
+    let holders: Vec<F> = qualified.iter().map(|holder| holder_points[(holder - 1) as usize]).collect();
+    let mut misdealt: BTreeSet<u64> = BTreeSet::new();
+    for (k, point) in secret_points.iter().enumerate() {
+        let mut syndromes: Vec<F> = vec![F::ZERO; base.len()];
+        for (holder, weights) in &checked {
+            let shares: Vec<F> = receivers.iter()
+                .map(|j| {
+                    let values = &held[j];
+                    values[holder][k] - base.iter().zip(weights).map(|(c, weight)| *weight * values[c][k]).sum::<F>()
+                })
+                .collect();
+            let (syndrome, _) = Poly::decode(&receiver_points, &shares, next_degree)?;
+            syndromes.push(syndrome.eval(*point));
+        }
+        let (_, errors) = Poly::decode(&holders, &syndromes, degree)?;
+        misdealt.extend(errors.into_iter().map(|position| qualified[position]));
+    }
+    Ok(misdealt)
+}
+
+///Passes the secrets `holding` shares among the receivers of `pp` on to the receivers of `next`.
+///Every holder deals its values; as in the beacon a dealing with at most t complaints qualifies, and degree+1 must.
+///Qualified holders that dealt values off the old sharing are found and left out.
+///Party i of `byzantine` is holder i as well as receiver i of `next`.
+pub fn reshare<F: Field, A: Adversary>(holding: &Holding<F>, pp: &PubParams, next: &PubParams, seed: u64, byzantine: &mut A) -> Result<Handover<F>> {
+    if next.packing != pp.packing {
+        return Err(Error::InvalidParams(format!("{} packed secrets cannot be passed to a committee sharing {}", pp.packing, next.packing)));
+    }
+    let mut rng: StdRng = StdRng::seed_from_u64(seed);
+
+    let mut dealings: Vec<(u64, VSS<F>, Sharing<F>, CostReport)> = Vec::new();
+    for (holder, values) in &holding.shares {
+        let mut values = values.clone();
+        if byzantine.is_corrupted(*holder) {
+            byzantine.tamper_reshared(*holder, &mut values);
+        }
+        let vss: VSS<F> = VSS {
+            secrets: values,
+            pp: pp.dealing_to(*holder, next)?,
+            execution_leaks: false,
+            seed: rng.gen()
+        };
+        let mut costs = CostReport::default();
+        let sharing = vss.share_phase(&mut costs, None, &mut DealingView { adversary: byzantine, dealer: *holder }, None)?;
+        dealings.push((*holder, vss, sharing, costs));
+    }
+
+    let complaints: BTreeMap<u64, u64> = dealings.iter().map(|(holder, _, sharing, _)| (*holder, sharing.complaints)).collect();
+    let qualified = Resolver { t: next.t }.qualify(&complaints);
+    let needed = pp.degree() + 1;
+    if (qualified.len() as u64) < needed {
+        return Err(Error::NotEnoughShares { needed: needed, verified: qualified.len() as u64 });
+    }
+
+    //What each new receiver holds from the qualified dealings; one that did not keep a row from all of them holds nothing
+    let (next_points, secret_points) = points_of::<F>(&next.roles.signing_keys()[1..], next.packing)?;
+    let mut held: BTreeMap<u64, BTreeMap<u64, Vec<F>>> = BTreeMap::new();
+    'receivers: for j in 1..=next.n {
+        let mut values: BTreeMap<u64, Vec<F>> = BTreeMap::new();
+        for (holder, _, sharing, _) in dealings.iter().filter(|(holder, _, _, _)| qualified.contains(holder)) {
+            match sharing.rows.get(&j) {
+                Some(row) => values.insert(*holder, evaluate_row(&next_points, row, next.degree(), &secret_points)?),
+                None => continue 'receivers,
+            };
+        }
+        held.insert(j, values);
+    }
+
+    let mut checking = CostReport::default();
+    let misdealt = misdealt_holders(&qualified, &holding.points, &held, pp.degree(), &next_points, &secret_points, next.degree(), &mut checking)?;
+    let chosen: Vec<u64> = qualified.iter().filter(|holder| !misdealt.contains(holder)).cloned().collect();
+    if (chosen.len() as u64) < needed {
+        return Err(Error::NotEnoughShares { needed: needed, verified: chosen.len() as u64 });
+    }
+
+    //Slot k of the secrets is the k-th column at the k-th secret point, interpolated from the chosen holders
+    let chosen_points: Vec<F> = chosen.iter().map(|holder| holding.points[(holder - 1) as usize]).collect();
+    let weights: Vec<Vec<F>> = secret_points.iter().map(|point| lagrange_weights(&chosen_points, *point)).collect();
+    let shares: BTreeMap<u64, Vec<F>> = held.iter()
+        .map(|(j, values)| {
+            let recombined = (0..next.packing as usize)
+                .map(|k| chosen.iter().enumerate().map(|(c, holder)| weights[k][c] * values[holder][k]).sum())
+                .collect();
+            (*j, recombined)
+        })
+        .collect();
+
+    let mut costs_per_dealing: BTreeMap<u64, CostReport> = BTreeMap::new();
+    let mut combined: Vec<(Vec<F>, VSS<F>, Sharing<F>)> = Vec::new();
+    for (holder, vss, sharing, costs) in dealings {
+        if let Some(c) = chosen.iter().position(|chosen| *chosen == holder) {
+            combined.push((weights.iter().map(|slot| slot[c]).collect(), vss, sharing));
+        }
+        costs_per_dealing.insert(holder, costs);
+    }
+
+    Ok(Handover {
+        qualified: qualified,
+        complaints: complaints,
+        misdealt: misdealt,
+        dealings: costs_per_dealing,
+        checking: checking,
+        holding: Holding { points: next_points, shares: shares },
+        combined: combined
+    })
+}
+
+impl<F: Field> Handover<F> {
+    ///Opens the secrets the new committee holds by reconstructing the recombined dealings; false if one of them cannot be
+    pub fn reconstruct<M: Metrics, A: Adversary>(&self, metrics: &mut M, byzantine: &mut A) -> Result<(bool, Vec<F>)> {
+        let mut reconstructed = true;
+        let mut secrets: Vec<F> = Vec::new();
+        for (weights, vss, sharing) in &self.combined {
+            let (dealing_reconstructed, dealt) = vss.reconstruct_phase(sharing, metrics, None, byzantine, None)?;
+            reconstructed &= dealing_reconstructed;
+            secrets.resize(dealt.len(), F::ZERO);
+            for ((secret, weight), value) in secrets.iter_mut().zip(weights).zip(dealt) {
+                *secret += *weight * value;
+            }
+        }
+        Ok((reconstructed, secrets))
+    }
+
+    pub fn total(&self) -> RoleCost {
+        let mut total = RoleCost::default();
+        for costs in self.dealings.values().chain(std::iter::once(&self.checking)) {
+            let dealing = costs.total();
+            total.messages += dealing.messages;
+            total.bytes += dealing.bytes;
+            total.time += dealing.time;
+        }
+        total
+    }
+}
+
+impl<F: Field> Resharing<F> {
+    ///Runs every epoch with the parties `byzantine` corrupts; party i is receiver i of every committee and holder i when that committee reshares
+    pub fn execute_against<A: Adversary>(&self, byzantine: &mut A) -> Result<ResharingOutcome<F>> {
+        let start_time = SystemTime::now();
+        let mut rng: StdRng = StdRng::seed_from_u64(self.dealing.seed);
+
+        let mut dealing = CostReport::default();
+        let sharing = self.dealing.share_phase(&mut dealing, None, byzantine, None)?;
+        let first = Holding::of(&sharing, &self.dealing.pp)?;
+
+        //Committee e+1 is nominated once committee e holds the secrets
+        let mut committees: Vec<PubParams> = Vec::new();
+        let mut handovers: Vec<Handover<F>> = Vec::new();
+        for _ in 0..self.epochs {
+            let current = committees.last().unwrap_or(&self.dealing.pp);
+            let holding = handovers.last().map_or(&first, |handover| &handover.holding);
+            let next = current.next_committee()?;
+            let handover = reshare(holding, current, &next, rng.gen(), byzantine)?;
+            committees.push(next);
+            handovers.push(handover);
+        }
+
+        let mut opening = CostReport::default();
+        let (reconstructed, secrets) = match handovers.last() {
+            Some(handover) => handover.reconstruct(&mut opening, byzantine)?,
+            None => self.dealing.reconstruct_phase(&sharing, &mut opening, None, byzantine, None)?,
+        };
+        Ok(ResharingOutcome {
+            success: reconstructed && secrets == self.dealing.secrets,
+            secrets: secrets,
+            reconstructed: reconstructed,
+            dealing: dealing,
+            handovers: handovers,
+            opening: opening,
+            time: SystemTime::now().duration_since(start_time).unwrap()
+        })
+    }
+}
+
+impl<F: Field> ResharingOutcome<F> {
+    pub fn print(&self) {
+        let dealing = self.dealing.total();
+        println!("First dealing: time {} ms, comm {} MB", dealing.time.as_millis(), (dealing.bytes as f64)/1000000.0);
+        println!("epoch | holder | complaints | qualified | misdealt | time ms | comm MB");
+        for (epoch, handover) in self.handovers.iter().enumerate() {
+            for (holder, costs) in &handover.dealings {
+                let total = costs.total();
+                println!("{} | {} | {} | {} | {} | {} | {}", epoch + 1, holder, handover.complaints.get(holder).copied().unwrap_or_default(), handover.qualified.contains(holder), handover.misdealt.contains(holder), total.time.as_millis(), (total.bytes as f64)/1000000.0);
+            }
+            let total = handover.total();
+            println!("Epoch {}: time {} ms, comm {} MB", epoch + 1, total.time.as_millis(), (total.bytes as f64)/1000000.0);
+        }
+        println!("Whole protocol takes {} milliseconds", self.time.as_millis());
+        println!("Secrets reconstructed: {}, secrets: {}, success: {}", self.reconstructed, display_values(&self.secrets), self.success);
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, KEYPAIR_LENGTH};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+
+use crate::communication::{encrypt, Ciphertext, EncryptionKey, RoleKey};
+use crate::error::{Error, Result};
+
+//YOSO role assignment: a role is known only by its public keys. For every role of a future committee the
+//nominating committee samples fresh keys, encrypts the secret halves to the long-term key of a random party
+//and publishes the public halves next to the ciphertext. The ciphertext does not reveal its recipient,
+//so the adversary cannot tell who holds a role before the role has spoken.
+//The committee's randomness is a commit-then-reveal coin: every member commits to a random contribution, then
+//reveals it, and the keys and holders are drawn from the hash of the contributions that match their commitments.
+//With t+1 members one of them is honest, and since nobody saw its contribution before committing, nobody can bias the hash.
+
+///Keys a role is known by: it signs with `signing`, and private messages to it are encrypted to `encryption`
+#[derive(Clone, Copy)]
+pub struct RolePublicKeys {
+    pub signing: PublicKey,
+    pub encryption: EncryptionKey,
+}
+
+///Secret keys of a role, held by the party nominated for it
+pub struct RoleKeys {
+    pub keypair: Keypair,
+    pub role_key: RoleKey,
+}
+
+//Sealed payload: the role index, the signing key pair and the role key
+const SEALED_LENGTH: usize = 8 + KEYPAIR_LENGTH + 32;
+
+///Ed25519 key pair whose secret key is 32 bytes drawn from `rng`
+pub fn generate_keypair<R: Rng>(rng: &mut R) -> Keypair {
+    let secret = SecretKey::from_bytes(&rng.gen::<[u8; 32]>()).expect("every 32 bytes are an Ed25519 secret key");
+    Keypair { public: PublicKey::from(&secret), secret: secret }
+}
+
+impl RoleKeys {
+    fn generate<R: Rng>(rng: &mut R) -> RoleKeys {
+        RoleKeys { keypair: generate_keypair(rng), role_key: RoleKey::generate(rng) }
+    }
+
+    pub fn public(&self) -> RolePublicKeys {
+        RolePublicKeys { signing: self.keypair.public, encryption: self.role_key.public() }
+    }
+
+    fn to_bytes(&self, role: u64) -> Vec<u8> {
+        let mut bytes = role.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.keypair.to_bytes());
+        bytes.extend_from_slice(&self.role_key.to_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<(u64, RoleKeys)> {
+        if bytes.len() != SEALED_LENGTH {
+            return None;
+        }
+        let (role, rest) = bytes.split_at(8);
+        let (keypair, role_key) = rest.split_at(KEYPAIR_LENGTH);
+        let keys = RoleKeys {
+            keypair: Keypair::from_bytes(keypair).ok()?,
+            role_key: RoleKey::from_bytes(role_key.try_into().ok()?),
+        };
+        Some((u64::from_le_bytes(role.try_into().ok()?), keys))
+    }
+}
+
+///What the nominating committee publishes for one role
+#[derive(Clone)]
+pub struct Nomination {
+    pub keys: RolePublicKeys,
+    sealed: Ciphertext,
+}
+
+///Published nominations of one committee, indexed by role
+#[derive(Clone, Default)]
+pub struct RoleAssignment {
+    pub nominations: BTreeMap<u64, Nomination>,
+}
+
+impl RoleAssignment {
+    ///Signing keys of all roles in role order
+    pub fn signing_keys(&self) -> Vec<PublicKey> {
+        self.nominations.values().map(|nomination| nomination.keys.signing).collect()
+    }
+
+    ///Encryption keys of all roles in role order
+    pub fn encryption_keys(&self) -> Vec<EncryptionKey> {
+        self.nominations.values().map(|nomination| nomination.keys.encryption).collect()
+    }
+}
+
+///Physical party; nominations are encrypted to its long-term key
+pub struct Party {
+    long_term: RoleKey,
+}
+
+impl Party {
+    pub fn generate<R: Rng>(rng: &mut R) -> Party {
+        Party { long_term: RoleKey::generate(rng) }
+    }
+
+    pub fn long_term_key(&self) -> EncryptionKey {
+        self.long_term.public()
+    }
+
+    ///Secret keys of `role` if this party was nominated for it; a sealed payload that does not match the published keys is ignored
+    pub fn open(&self, role: u64, nomination: &Nomination) -> Option<RoleKeys> {
+        let bytes = self.long_term.decrypt(&nomination.sealed).ok()?;
+        let (sealed_role, keys) = RoleKeys::from_bytes(&bytes)?;
+        let public = keys.public();
+        if sealed_role != role || public.signing != nomination.keys.signing || public.encryption.as_bytes() != nomination.keys.encryption.as_bytes() {
+            return None;
+        }
+        Some(keys)
+    }
+}
+
+///Random contribution of one nominating member: first the commitment is published, then the randomness
+#[derive(Clone)]
+pub struct Contribution {
+    pub commitment: [u8; 32],
+    pub randomness: [u8; 32],
+}
+
+impl Contribution {
+    pub fn generate<R: Rng>(rng: &mut R) -> Contribution {
+        let randomness: [u8; 32] = rng.gen();
+        Contribution { commitment: Sha256::digest(randomness).into(), randomness: randomness }
+    }
+
+    fn opens(&self) -> bool {
+        <[u8; 32]>::from(Sha256::digest(self.randomness)) == self.commitment
+    }
+}
+
+///Committee nominating the holders of a future committee's roles, one contribution per member
+pub struct NominatingCommittee {
+    pub contributions: Vec<Contribution>,
+}
+
+impl NominatingCommittee {
+    ///A committee of `members` members, each drawing its contribution from `rng`
+    pub fn generate<R: Rng>(members: u64, rng: &mut R) -> NominatingCommittee {
+        NominatingCommittee { contributions: (0..members).map(|_| Contribution::generate(rng)).collect() }
+    }
+
+    //Hash of the revealed contributions that open their commitments; a member revealing anything else is left out
+    fn coin(&self) -> Option<[u8; 32]> {
+        let opened: Vec<&Contribution> = self.contributions.iter().filter(|contribution| contribution.opens()).collect();
+        if opened.is_empty() {
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        for contribution in opened {
+            hasher.update(contribution.randomness);
+        }
+        Some(hasher.finalize().into())
+    }
+
+    ///Nominates a uniformly random party for each of the roles 0..roles, with keys and holders drawn from the committee's coin.
+    ///`parties` are the long-term keys of the parties to choose from.
+    pub fn nominate(&self, roles: u64, parties: &[EncryptionKey]) -> Result<RoleAssignment> {
+        if parties.is_empty() {
+            return Err(Error::InvalidParams("roles need parties to hold them".to_string()));
+        }
+        let coin = self.coin().ok_or_else(|| Error::InvalidParams("no member of the nominating committee opened its commitment".to_string()))?;
+        let mut rng = StdRng::from_seed(coin);
+        let mut nominations: BTreeMap<u64, Nomination> = BTreeMap::new();
+        for role in 0..roles {
+            let keys = RoleKeys::generate(&mut rng);
+            let holder = &parties[rng.gen_range(0..parties.len())];
+            nominations.insert(role, Nomination {
+                keys: keys.public(),
+                sealed: encrypt(holder, &keys.to_bytes(role))?,
+            });
+        }
+        Ok(RoleAssignment { nominations: nominations })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parties(rng: &mut StdRng) -> Vec<Party> {
+        (0..3).map(|_| Party::generate(rng)).collect()
+    }
+
+    #[test]
+    fn the_same_contributions_nominate_the_same_roles() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let parties = parties(&mut rng);
+        let long_term_keys: Vec<EncryptionKey> = parties.iter().map(|party| party.long_term_key()).collect();
+        let committee = NominatingCommittee::generate(2, &mut rng);
+        let assignment = committee.nominate(4, &long_term_keys).unwrap();
+        assert_eq!(assignment.signing_keys(), committee.nominate(4, &long_term_keys).unwrap().signing_keys());
+        for (role, nomination) in &assignment.nominations {
+            assert_eq!(parties.iter().filter(|party| party.open(*role, nomination).is_some()).count(), 1);
+            assert!(parties.iter().all(|party| party.open(role + 1, nomination).is_none()));
+        }
+        let other = NominatingCommittee::generate(2, &mut rng).nominate(4, &long_term_keys).unwrap();
+        assert_ne!(assignment.signing_keys(), other.signing_keys());
+    }
+
+    #[test]
+    fn a_contribution_that_does_not_open_its_commitment_is_left_out() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let long_term_keys: Vec<EncryptionKey> = parties(&mut rng).iter().map(|party| party.long_term_key()).collect();
+        let mut committee = NominatingCommittee::generate(3, &mut rng);
+        let honest = NominatingCommittee { contributions: committee.contributions[1..].to_vec() };
+        committee.contributions[0].randomness[0] ^= 1;
+        assert_eq!(committee.nominate(2, &long_term_keys).unwrap().signing_keys(), honest.nominate(2, &long_term_keys).unwrap().signing_keys());
+
+        for contribution in committee.contributions.iter_mut() {
+            contribution.randomness[0] ^= 1;
+        }
+        committee.contributions[0].randomness[0] ^= 1;
+        assert!(committee.nominate(2, &long_term_keys).is_err());
+        assert!(NominatingCommittee { contributions: Vec::new() }.nominate(2, &long_term_keys).is_err());
+    }
+}
@@ -0,0 +1,472 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ark_bls12_381::Fq;
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+
+use crate::adversary::{Adversary, Behavior, Corruption, Honest};
+use crate::auditor::Auditor;
+use crate::board::{Checkpoint, FileBoard};
+use crate::broadcast::ReliableBroadcast;
+use crate::communication::{BroadcastMessage, BulletinBoard};
+use crate::reshare::Resharing;
+use crate::dkg::Dkg;
+use crate::rand_extr::{ExtractorOutcome, ExtractorParams, RandomnessExtractor};
+use crate::transcript::Deviation;
+use crate::vss::{publications_of, Authentication, PubParams, RandExtractorVSSBased, VssReport, VSS};
+
+//Fault-injection suite: each scenario corrupts some parties and checks that the honest parties
+//still agree on the output and that the right complaint path fired.
+//`cargo test` runs every scenario for t = 1 and 2, the `scenarios` command for any t.
+
+fn check(condition: bool, failure: &str) -> Result<(), String> {
+    if condition { Ok(()) } else { Err(failure.to_string()) }
+}
+
+fn corrupt(t: u64, parties: &[(u64, Behavior)]) -> Result<Corruption, String> {
+    let corruption = parties.iter().try_fold(Corruption::new(t), |corruption, (party, behavior)| corruption.corrupt(*party, *behavior))?;
+    Ok(corruption)
+}
+
+fn run_vss<A: Adversary>(t: u64, seed: u64, adversary: &mut A) -> Result<VssReport<Fq>, String> {
+    let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(seed + 1)], pp: PubParams::new(t)?, execution_leaks: false, seed: seed };
+    Ok(vss.execute_against(adversary)?)
+}
+
+fn run_extractor<A: Adversary>(t: u64, seed: u64, adversary: &mut A) -> Result<ExtractorOutcome, String> {
+    let rand_extr = RandomnessExtractor { params: ExtractorParams::new(t as usize), seed: Some(seed) };
+    Ok(rand_extr.execute_against(adversary)?)
+}
+
+//Every subset gets the same result from all honest publishers
+fn honest_publishers_agree<A: Adversary>(outcome: &ExtractorOutcome, adversary: &A) -> bool {
+    let mut results: BTreeMap<usize, BTreeSet<i32>> = BTreeMap::new();
+    for (publisher, published) in &outcome.published {
+        if adversary.is_corrupted(*publisher as u64) {
+            continue;
+        }
+        for (subset_index, value) in published {
+            results.entry(*subset_index).or_default().insert(*value);
+        }
+    }
+    results.values().all(|values| values.len() == 1)
+}
+
+fn honest_vss(t: u64, seed: u64) -> Result<(), String> {
+    let report = run_vss(t, seed, &mut Honest)?;
+    check(report.success, "the secret was not reconstructed")?;
+    check(report.complaints == 0 && report.accusations.is_empty(), "an honest run raised complaints")
+}
+
+fn dealer_bad_degree_row(t: u64, seed: u64) -> Result<(), String> {
+    let report = run_vss(t, seed, &mut corrupt(t, &[(0, Behavior::BadDegreeRow)])?)?;
+    check(report.complaints > 0, "nobody complained about the dealer")?;
+    check(report.success, "the remaining rows did not reconstruct the secret")
+}
+
+fn dealer_silent(t: u64, seed: u64) -> Result<(), String> {
+    let report = run_vss(t, seed, &mut corrupt(t, &[(0, Behavior::Silent)])?)?;
+    check(report.complaints == PubParams::standard_size(t).0, "not every receiver complained about the missing rows")?;
+    check(!report.reconstructed, "a secret was reconstructed without any rows")
+}
+
+fn receiver_wrong_forward(t: u64, seed: u64) -> Result<(), String> {
+    let report = run_vss(t, seed, &mut corrupt(t, &[(1, Behavior::WrongForward)])?)?;
+    check(report.accusations.keys().eq([1].iter()), "the lying receiver was not the one accused")?;
+    check(report.complaints == 0, "the honest dealer was blamed")?;
+    check(report.success, "the secret was not reconstructed")
+}
+
+fn receiver_silent(t: u64, seed: u64) -> Result<(), String> {
+    let report = run_vss(t, seed, &mut corrupt(t, &[(2, Behavior::Silent)])?)?;
+    check(report.accusations.keys().eq([2].iter()), "the silent receiver was not the one accused")?;
+    check(report.success, "the secret was not reconstructed")
+}
+
+fn receiver_strips_signatures(t: u64, seed: u64) -> Result<(), String> {
+    let report = run_vss(t, seed, &mut corrupt(t, &[(1, Behavior::Garbage)])?)?;
+    check(report.accusations.keys().eq([1].iter()), "the receiver sending unsigned subshares was not the one accused")?;
+    check(report.success, "the secret was not reconstructed")
+}
+
+fn beacon_excludes_silent_dealer(t: u64, seed: u64) -> Result<(), String> {
+    let beacon: RandExtractorVSSBased<Fq> = RandExtractorVSSBased::new(PubParams::new(t)?, false, Some(seed));
+    let outcome = beacon.execute_against(&mut corrupt(t, &[(1, Behavior::Silent)])?)?;
+    check(!outcome.qualified.contains(&1), "the silent dealer was qualified")?;
+    check(outcome.qualified.len() as u64 == PubParams::standard_size(t).0 - 1, "an honest dealer was disqualified")?;
+    check(outcome.success, "a qualified dealing was not reconstructed")
+}
+
+fn beacon_survives_corrupted_reconstructors(t: u64, seed: u64) -> Result<(), String> {
+    let n = PubParams::standard_size(t).0;
+    let beacon: RandExtractorVSSBased<Fq> = RandExtractorVSSBased::new(PubParams::new(t)?, false, Some(seed));
+    let honest = beacon.execute()?;
+    //All but one reconstructor publish nothing or altered subshares in every dealing
+    let reconstructors: Vec<(u64, Behavior)> = (1..=t).map(|j| (n + j, if j % 2 == 1 { Behavior::Silent } else { Behavior::WrongForward })).collect();
+    let outcome = beacon.execute_against(&mut corrupt(t, &reconstructors)?)?;
+    check(outcome.qualified.len() as u64 == n, "an honest dealer was disqualified")?;
+    check(outcome.success && outcome.output == honest.output, "the client did not reconstruct from the honest reconstructor")
+}
+
+fn honest_extractor(t: u64, seed: u64) -> Result<(), String> {
+    let outcome = run_extractor(t, seed, &mut Honest)?;
+    check(honest_publishers_agree(&outcome, &Honest), "honest publishers disagree")?;
+    check(outcome.complaints.values().all(|complaints| *complaints == 0), "an honest run raised complaints")
+}
+
+fn leader_equivocates(t: u64, seed: u64) -> Result<(), String> {
+    let mut adversary = corrupt(t, &[(1, Behavior::Equivocate)])?;
+    let outcome = run_extractor(t, seed, &mut adversary)?;
+    check(outcome.complaints.values().any(|complaints| *complaints > 0), "nobody noticed the equivocation")?;
+    check(honest_publishers_agree(&outcome, &adversary), "honest publishers disagree")
+}
+
+fn leader_sends_garbage(t: u64, seed: u64) -> Result<(), String> {
+    let mut adversary = corrupt(t, &[(1, Behavior::Garbage)])?;
+    let outcome = run_extractor(t, seed, &mut adversary)?;
+    let marked = outcome.faulty.iter().filter(|(verifier, _)| !adversary.is_corrupted(**verifier as u64)).all(|(_, faulty)| faulty.contains(&1));
+    check(marked, "an honest verifier did not mark the leader as faulty")?;
+    check(honest_publishers_agree(&outcome, &adversary), "honest publishers disagree")
+}
+
+fn publisher_lies(t: u64, seed: u64) -> Result<(), String> {
+    let honest = run_extractor(t, seed, &mut Honest)?;
+    let mut adversary = corrupt(t, &[(1, Behavior::LyingPublisher)])?;
+    let outcome = run_extractor(t, seed, &mut adversary)?;
+    check(honest_publishers_agree(&outcome, &adversary), "honest publishers disagree")?;
+    check(outcome.coin == honest.coin, "a single publisher changed the coin")
+}
+
+fn verifier_silent(t: u64, seed: u64) -> Result<(), String> {
+    let mut adversary = corrupt(t, &[(2, Behavior::Silent)])?;
+    let outcome = run_extractor(t, seed, &mut adversary)?;
+    check(honest_publishers_agree(&outcome, &adversary), "honest publishers disagree")
+}
+
+//Every deviation a replay found is blamed on a party `culprit` accepts
+fn deviations_blame(deviations: &[Deviation], culprit: impl Fn(&Deviation) -> bool) -> bool {
+    !deviations.is_empty() && deviations.iter().all(culprit)
+}
+
+fn vss_transcript_blames_receiver(t: u64, seed: u64) -> Result<(), String> {
+    let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(seed + 1)], pp: PubParams::new(t)?, execution_leaks: false, seed: seed };
+    let (report, transcript) = vss.execute_recorded(&mut corrupt(t, &[(1, Behavior::WrongForward)])?)?;
+    let verification = VSS::<Fq>::verify_transcript(&transcript)?;
+    check(verification.output_matches() && verification.output == report.secrets, "the replay recovered a different secret")?;
+    check(deviations_blame(&verification.deviations, |deviation| deviation.party == 1), "the replay did not single out the lying receiver")
+}
+
+fn beacon_transcript_blames_dealer(t: u64, seed: u64) -> Result<(), String> {
+    let beacon: RandExtractorVSSBased<Fq> = RandExtractorVSSBased::new(PubParams::new(t)?, false, Some(seed));
+    //Party 1 deals nothing in its own dealing and sends nothing as receiver 1 in all others
+    let (outcome, mut transcript) = beacon.execute_recorded(&mut corrupt(t, &[(1, Behavior::Silent)])?)?;
+    let verification = RandExtractorVSSBased::<Fq>::verify_transcript(&transcript)?;
+    check(verification.output_matches() && verification.output == outcome.output, "the replay computed a different beacon value")?;
+    check(deviations_blame(&verification.deviations, |deviation| deviation.party == 1 || (deviation.party == 0 && deviation.session == 1)), "the replay blamed an honest party")?;
+
+    let mut wrong_output = outcome.output.clone();
+    wrong_output[0] += Fq::ONE;
+    transcript.output.clear();
+    wrong_output.serialize_compressed(&mut transcript.output).map_err(|error| error.to_string())?;
+    check(!RandExtractorVSSBased::<Fq>::verify_transcript(&transcript)?.output_matches(), "a wrong beacon value was accepted")
+}
+
+fn extractor_transcript_blames_publisher(t: u64, seed: u64) -> Result<(), String> {
+    let rand_extr = RandomnessExtractor { params: ExtractorParams::new(t as usize), seed: Some(seed) };
+    let (outcome, transcript) = rand_extr.execute_recorded(&mut corrupt(t, &[(1, Behavior::LyingPublisher)])?)?;
+    let verification = RandomnessExtractor::verify_transcript(&transcript)?;
+    check(verification.output_matches() && verification.output == outcome.coin, "the replay computed a different coin")?;
+    check(deviations_blame(&verification.deviations, |deviation| deviation.party == 1 && deviation.round == 4), "the replay did not single out the lying publisher")
+}
+
+fn auditor_checks_claimed_secret(t: u64, seed: u64) -> Result<(), String> {
+    let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(seed + 1)], pp: PubParams::new(t)?, execution_leaks: false, seed: seed };
+    let (report, transcript) = vss.execute_recorded(&mut Honest)?;
+    let n = PubParams::standard_size(t).0;
+    let pki = transcript.keys_of(0)?;
+    let mut publications = publications_of::<Fq>(&transcript, 0, t, n);
+    let auditor = Auditor::new(t, n);
+    let mut wrong_secrets = report.secrets.clone();
+    wrong_secrets[0] += Fq::ONE;
+    check(auditor.audit(&pki, &publications, &report.secrets)?.passed, "the auditor rejected the reconstructed secret")?;
+    check(!auditor.audit(&pki, &publications, &wrong_secrets)?.passed, "the auditor accepted a wrong secret")?;
+
+    //A reconstructor that alters every subshare of row 1 gets that row rejected, but the secret still opens
+    for row in publications[0].values_mut() {
+        if let Some(subshare) = row.get_mut(&1) {
+            subshare.value += Fq::ONE;
+        }
+    }
+    let verdict = auditor.audit(&pki, &publications, &report.secrets)?;
+    check(verdict.passed, "an altered row made the auditor reject the secret")?;
+    check(verdict.reasons.iter().any(|reason| reason.starts_with("publication 1, row 1:")), "the auditor did not report the altered row")
+}
+
+fn merkle_vss_blames_receiver(t: u64, seed: u64) -> Result<(), String> {
+    let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(seed + 1)], pp: PubParams::new(t)?.with_authentication(Authentication::Merkle), execution_leaks: false, seed: seed };
+    let (report, transcript) = vss.execute_recorded(&mut Honest)?;
+    check(report.success && report.complaints == 0 && report.accusations.is_empty(), "an honest run with Merkle paths did not go through cleanly")?;
+    check(VSS::<Fq>::verify_transcript(&transcript)?.deviations.is_empty(), "the replay flagged an honest run with Merkle paths")?;
+
+    //The liar's own endorsements verify, but the dealer's paths do not lead to the signed root for the changed values
+    let report = vss.execute_against(&mut corrupt(t, &[(1, Behavior::WrongForward)])?)?;
+    check(report.accusations.keys().eq([1].iter()), "the lying receiver was not the one accused")?;
+    check(report.complaints == 0, "the honest dealer was blamed")?;
+    check(report.success, "the secret was not reconstructed")
+}
+
+fn packed_vss_survives_silent_receivers(t: u64, seed: u64) -> Result<(), String> {
+    let packing = t + 1;
+    let secrets: Vec<Fq> = (1..=packing).map(|k| Fq::from(seed + k)).collect();
+    let vss: VSS<Fq> = VSS { secrets: secrets.clone(), pp: PubParams::packed(t, packing)?, execution_leaks: false, seed: seed };
+    let silent: Vec<(u64, Behavior)> = (1..=t).map(|receiver| (receiver, Behavior::Silent)).collect();
+    let report = vss.execute_against(&mut corrupt(t, &silent)?)?;
+    check(report.complaints == 0, "the honest dealer was blamed")?;
+    check(report.success && report.secrets == secrets, "the packed secrets were not all reconstructed")
+}
+
+fn resharing_survives_silent_holders(t: u64, seed: u64) -> Result<(), String> {
+    let secrets: Vec<Fq> = vec![Fq::from(seed + 1), Fq::from(seed + 2)];
+    let resharing: Resharing<Fq> = Resharing {
+        dealing: VSS { secrets: secrets.clone(), pp: PubParams::packed(t, 2)?, execution_leaks: false, seed: seed },
+        epochs: 2,
+    };
+    //The silent parties hold shares after every dealing but never pass them on
+    let silent: Vec<(u64, Behavior)> = (1..=t).map(|party| (party, Behavior::Silent)).collect();
+    let outcome = resharing.execute_against(&mut corrupt(t, &silent)?)?;
+    check(outcome.handovers.iter().all(|handover| handover.qualified.iter().all(|holder| *holder > t)), "a silent holder's dealing qualified")?;
+    check(outcome.success && outcome.secrets == secrets, "the last committee did not open the dealt secrets")
+}
+
+fn resharing_drops_misdealing_holders(t: u64, seed: u64) -> Result<(), String> {
+    let secrets: Vec<Fq> = vec![Fq::from(seed + 1), Fq::from(seed + 2)];
+    let resharing: Resharing<Fq> = Resharing {
+        dealing: VSS { secrets: secrets.clone(), pp: PubParams::packed(t, 2)?, execution_leaks: false, seed: seed },
+        epochs: 2,
+    };
+    //Their dealings are consistent, only the values dealt are not their shares
+    let liars: Vec<(u64, Behavior)> = (1..=t).map(|party| (party, Behavior::WrongShare)).collect();
+    let outcome = resharing.execute_against(&mut corrupt(t, &liars)?)?;
+    let expected: BTreeSet<u64> = (1..=t).collect();
+    check(outcome.handovers.iter().all(|handover| handover.misdealt == expected), "the misdealing holders were not the ones left out")?;
+    check(outcome.success && outcome.secrets == secrets, "the last committee did not open the dealt secrets")
+}
+
+fn dkg_disqualifies_wrong_commitment(t: u64, seed: u64) -> Result<(), String> {
+    let n = PubParams::standard_size(t).0;
+    let outcome = Dkg { pp: PubParams::new(t)?, seed: Some(seed) }.execute()?;
+    check(outcome.qualified.len() as u64 == n && outcome.shares.len() as u64 == n, "an honest dealer or receiver was left out")?;
+    check(outcome.success, "the key shares of an honest run do not match the public key")?;
+
+    //Dealer 1's rows are consistent, only its commitment is not
+    let mut corruption = corrupt(t, &[(1, Behavior::WrongCommitment)])?;
+    let outcome = Dkg { pp: PubParams::new(t)?, seed: Some(seed) }.execute_against(&mut corruption)?;
+    check(outcome.complaints.get(&1) == Some(&n), "not every receiver complained about the wrong commitment")?;
+    check(outcome.dealings[&1].rejections.len() as u64 == n, "the complaints about the commitment were not recorded")?;
+    check(!outcome.qualified.contains(&1) && outcome.qualified.len() as u64 == n - 1, "the qualified set is not the honest dealers")?;
+    check(outcome.success, "the key shares do not match the public key")
+}
+
+fn broadcast_survives_equivocation(t: u64, seed: u64) -> Result<(), String> {
+    let n = 3*t + 1;
+    let equivocators: Vec<(u64, Behavior)> = (1..=t).map(|party| (party, Behavior::Equivocate)).collect();
+    let mut adversary = corrupt(t, &equivocators)?;
+    let mut board = ReliableBroadcast::new(n, t, &mut adversary)?;
+    let messages: Vec<BroadcastMessage> = (1..=n).map(|sender| BroadcastMessage::new(sender, (seed + sender).to_le_bytes().to_vec())).collect();
+    board.store_round(1, &messages)?;
+
+    let views: Vec<_> = board.views().values().collect();
+    check(views.windows(2).all(|pair| pair[0] == pair[1]), "honest parties read different rounds")?;
+    let round = board.read_round(1).cloned().unwrap_or_default();
+    check(messages[t as usize..].iter().all(|message| round.contains(message)), "a message of an honest sender was not delivered")
+}
+
+fn board_detects_rewrite(t: u64, seed: u64) -> Result<(), String> {
+    let path = std::env::temp_dir().join(format!("yosowcc-board-{}-{}-{}.bin", std::process::id(), t, seed));
+    let _ = std::fs::remove_file(&path);
+    let result = (|| -> Result<(), String> {
+        let mut board = FileBoard::open(&path)?;
+        for round in 0..=t {
+            board.store_round(round, &[BroadcastMessage::new(round, (seed + round).to_le_bytes().to_vec())])?;
+        }
+        let checkpoint = board.checkpoint();
+        board.store_round(t + 1, &[BroadcastMessage::new(0, seed.to_le_bytes().to_vec())])?;
+        drop(board);
+        check(FileBoard::verify_from(&path, &checkpoint).is_ok(), "an untouched board does not verify from a checkpoint")?;
+        check(FileBoard::open(&path)?.read_round(t).map(|messages| messages.len()) == Some(1), "a reopened board lost a round")?;
+
+        //Rewrite the payload of round 0, which sits at a fixed offset in the first record
+        let mut bytes = std::fs::read(&path).map_err(|error| error.to_string())?;
+        bytes[8 + 32 + 8 + 8 + 8] ^= 1;
+        std::fs::write(&path, &bytes).map_err(|error| error.to_string())?;
+        check(FileBoard::open(&path).is_err(), "a rewritten round is not detected")?;
+        check(FileBoard::verify_from(&path, &Checkpoint::genesis()).is_err(), "a rewritten round is not detected from genesis")?;
+        //Records before a checkpoint are taken on trust
+        check(FileBoard::verify_from(&path, &checkpoint).is_ok(), "records after the checkpoint stopped verifying")
+    })();
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+///Runs every scenario and returns its name and result
+pub fn run_all(t: u64, seed: u64) -> Vec<(&'static str, Result<(), String>)> {
+    let scenarios: [(&'static str, fn(u64, u64) -> Result<(), String>); 24] = [
+        ("honest VSS", honest_vss),
+        ("VSS dealer sends a row not of degree t", dealer_bad_degree_row),
+        ("VSS dealer stays silent", dealer_silent),
+        ("VSS receiver forwards wrong values", receiver_wrong_forward),
+        ("VSS receiver stays silent", receiver_silent),
+        ("VSS receiver sends unsigned subshares", receiver_strips_signatures),
+        ("beacon excludes a silent dealer", beacon_excludes_silent_dealer),
+        ("beacon reconstructs past t corrupted reconstructors", beacon_survives_corrupted_reconstructors),
+        ("honest extractor", honest_extractor),
+        ("extractor leader equivocates", leader_equivocates),
+        ("extractor leader sends values that are not bits", leader_sends_garbage),
+        ("extractor publisher lies", publisher_lies),
+        ("extractor verifier stays silent", verifier_silent),
+        ("VSS transcript replay blames the lying receiver", vss_transcript_blames_receiver),
+        ("beacon transcript replay blames the silent party", beacon_transcript_blames_dealer),
+        ("extractor transcript replay blames the lying publisher", extractor_transcript_blames_publisher),
+        ("auditor checks the claimed secret", auditor_checks_claimed_secret),
+        ("VSS with Merkle paths blames the lying receiver", merkle_vss_blames_receiver),
+        ("packed VSS with t silent receivers", packed_vss_survives_silent_receivers),
+        ("resharing past t silent holders keeps the secrets", resharing_survives_silent_holders),
+        ("resharing leaves out t holders dealing wrong values", resharing_drops_misdealing_holders),
+        ("DKG disqualifies a dealer with a wrong commitment", dkg_disqualifies_wrong_commitment),
+        ("reliable broadcast with equivocating senders", broadcast_survives_equivocation),
+        ("bulletin board detects a rewritten round", board_detects_rewrite),
+    ];
+    scenarios.iter().map(|(name, scenario)| (*name, scenario(t, seed))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    //Every scenario runs against the smallest committees, with a single and with two corrupted parties
+    fn passes(scenario: fn(u64, u64) -> Result<(), String>) {
+        for t in 1..=2 {
+            if let Err(failure) = scenario(t, 6) {
+                panic!("t = {}: {}", t, failure);
+            }
+        }
+    }
+
+    #[test]
+    fn resharing_survives_silent_holders() {
+        passes(super::resharing_survives_silent_holders);
+    }
+
+    #[test]
+    fn resharing_drops_misdealing_holders() {
+        passes(super::resharing_drops_misdealing_holders);
+    }
+
+    #[test]
+    fn dkg_disqualifies_wrong_commitment() {
+        passes(super::dkg_disqualifies_wrong_commitment);
+    }
+
+    #[test]
+    fn honest_vss() {
+        passes(super::honest_vss);
+    }
+
+    #[test]
+    fn dealer_bad_degree_row() {
+        passes(super::dealer_bad_degree_row);
+    }
+
+    #[test]
+    fn dealer_silent() {
+        passes(super::dealer_silent);
+    }
+
+    #[test]
+    fn receiver_wrong_forward() {
+        passes(super::receiver_wrong_forward);
+    }
+
+    #[test]
+    fn receiver_silent() {
+        passes(super::receiver_silent);
+    }
+
+    #[test]
+    fn beacon_excludes_silent_dealer() {
+        passes(super::beacon_excludes_silent_dealer);
+    }
+
+    #[test]
+    fn beacon_survives_corrupted_reconstructors() {
+        passes(super::beacon_survives_corrupted_reconstructors);
+    }
+
+    #[test]
+    fn honest_extractor() {
+        passes(super::honest_extractor);
+    }
+
+    #[test]
+    fn leader_equivocates() {
+        passes(super::leader_equivocates);
+    }
+
+    #[test]
+    fn publisher_lies() {
+        passes(super::publisher_lies);
+    }
+
+    #[test]
+    fn verifier_silent() {
+        passes(super::verifier_silent);
+    }
+
+    #[test]
+    fn receiver_strips_signatures() {
+        passes(super::receiver_strips_signatures);
+    }
+
+    #[test]
+    fn leader_sends_garbage() {
+        passes(super::leader_sends_garbage);
+    }
+
+    #[test]
+    fn broadcast_survives_equivocation() {
+        passes(super::broadcast_survives_equivocation);
+    }
+
+    #[test]
+    fn board_detects_rewrite() {
+        passes(super::board_detects_rewrite);
+    }
+
+    #[test]
+    fn vss_transcript_blames_receiver() {
+        passes(super::vss_transcript_blames_receiver);
+    }
+
+    #[test]
+    fn beacon_transcript_blames_dealer() {
+        passes(super::beacon_transcript_blames_dealer);
+    }
+
+    #[test]
+    fn extractor_transcript_blames_publisher() {
+        passes(super::extractor_transcript_blames_publisher);
+    }
+
+    #[test]
+    fn auditor_checks_claimed_secret() {
+        passes(super::auditor_checks_claimed_secret);
+    }
+
+    #[test]
+    fn merkle_vss_blames_receiver() {
+        passes(super::merkle_vss_blames_receiver);
+    }
+
+    #[test]
+    fn packed_vss_survives_silent_receivers() {
+        passes(super::packed_vss_survives_silent_receivers);
+    }
+}
@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::{Error, Result};
+use crate::subsets::{binomial, rank, Combinations};
+
+//Above this many corruption sets the analyzer samples instead of enumerating, and covering designs are built from groups
+const EXACT_ANALYSIS_LIMIT: usize = 2_000_000;
+//Largest family a covering design is allowed to list
+const COVERING_DESIGN_LIMIT: usize = 2_000_000;
+
+///Family of subsets the extractor runs over
+#[derive(Clone, Debug)]
+pub enum SubsetFamily {
+    ///Every k-subset of the n parties, enumerated lazily through the combinatorial number system
+    Complete,
+    ///Explicit list of sorted k-subsets, e.g. from a covering design or random sampling
+    Explicit(Arc<Vec<Vec<usize>>>),
+}
+
+impl SubsetFamily {
+    pub fn explicit(subsets: Vec<Vec<usize>>) -> SubsetFamily {
+        SubsetFamily::Explicit(Arc::new(subsets))
+    }
+
+    ///Covering-design family: for every set T of t parties some subset avoids T.
+    ///Equivalently the complements of the subsets form a covering of all t-sets by (n-k)-sets.
+    ///With at most EXACT_ANALYSIS_LIMIT t-sets a greedy cover visits each of them; above that the family is
+    ///built from groups of parties (see `grouped_cover`), which avoids every t-set by construction without listing them.
+    pub fn covering_design(n: usize, k: usize, t: usize) -> Result<SubsetFamily> {
+        if k > n || t > n || n - t < k {
+            return Err(Error::InvalidParams(format!("with {} of {} parties corrupted no {}-subset can be fully honest", t, n, k)));
+        }
+        match binomial(n, t) {
+            Some(count) if count <= EXACT_ANALYSIS_LIMIT => Ok(SubsetFamily::explicit(greedy_cover(n, k, t, count))),
+            _ => Ok(SubsetFamily::explicit(grouped_cover(n, k, t)?)),
+        }
+    }
+
+    ///m uniformly random k-subsets of the n parties
+    pub fn sampled(n: usize, k: usize, m: usize, seed: u64) -> SubsetFamily {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let subsets: Vec<Vec<usize>> = (0..m)
+            .map(|_| {
+                let mut subset: Vec<usize> = random_subset(n, k, &mut rng);
+                subset.sort();
+                subset
+            })
+            .collect();
+        SubsetFamily::explicit(subsets)
+    }
+}
+
+//k distinct parties out of n, picked by a partial Fisher-Yates shuffle
+fn random_subset(n: usize, k: usize, rng: &mut StdRng) -> Vec<usize> {
+    let mut parties: Vec<usize> = (1..=n).collect();
+    for i in 0..k {
+        let j = rng.gen_range(i..n);
+        parties.swap(i, j);
+    }
+    parties.truncate(k);
+    parties
+}
+
+//Greedy cover over all `number_corruption_sets` t-sets: each t-set not yet avoided gets a subset of the parties in the fewest uncovered t-sets
+fn greedy_cover(n: usize, k: usize, t: usize, number_corruption_sets: usize) -> Vec<Vec<usize>> {
+    let mut covered: Vec<bool> = vec![false; number_corruption_sets];
+    //How many uncovered t-sets each party is part of; parties in many of them are kept out of new subsets
+    let mut uncovered_containing: Vec<usize> = vec![binomial(n.saturating_sub(1), t.saturating_sub(1)).unwrap_or_default(); n + 1];
+    let mut subsets: Vec<Vec<usize>> = Vec::new();
+
+    //Corruption sets come in lexicographic order, so their position is their rank
+    for (index, corruption_set) in Combinations::new((1..=n).collect(), t).enumerate() {
+        if covered[index] {
+            continue;
+        }
+
+        //Build a subset avoiding this corruption set out of the parties in the fewest uncovered t-sets
+        let mut candidates: Vec<usize> = (1..=n).filter(|i| !corruption_set.contains(i)).collect();
+        candidates.sort_by_key(|&i| uncovered_containing[i]);
+        let mut subset: Vec<usize> = candidates.into_iter().take(k).collect();
+        subset.sort();
+
+        //Every t-set inside the subset's complement is now avoided by the subset
+        let complement: Vec<usize> = (1..=n).filter(|i| !subset.contains(i)).collect();
+        for avoided in Combinations::new(complement, t) {
+            let index = rank(n, &avoided);
+            if !covered[index] {
+                covered[index] = true;
+                for party in &avoided {
+                    uncovered_containing[*party] -= 1;
+                }
+            }
+        }
+        subsets.push(subset);
+    }
+    subsets
+}
+
+//Splits the parties into t+r groups of nearly equal size, with r the fewest groups that together always hold k parties.
+//t corrupted parties fall into at most t groups, so r groups stay honest, and the first k parties of their union
+//form a subset of the family. The family takes one subset per choice of r groups, C(t+r, r) in all.
+fn grouped_cover(n: usize, k: usize, t: usize) -> Result<Vec<Vec<usize>>> {
+    //The r smallest of m groups hold r*(n/m) parties, plus one for each of them that got a remainder party
+    let smallest = |m: usize, r: usize| r * (n / m) + r.saturating_sub(m - n % m);
+    let r = (1..=n - t).find(|&r| smallest(t + r, r) >= k)
+        .ok_or_else(|| Error::InvalidParams(format!("no {}-subset of {} parties survives {} corruptions", k, n, t)))?;
+    let m = t + r;
+    match binomial(m, r) {
+        Some(count) if count <= COVERING_DESIGN_LIMIT => {},
+        _ => return Err(Error::InvalidParams(format!("a grouped covering design of {}-subsets of {} parties against {} corruptions needs C({}, {}) subsets, at most {} are listed", k, n, t, m, r, COVERING_DESIGN_LIMIT))),
+    }
+
+    //The first n % m groups take one party more
+    let mut groups: Vec<Vec<usize>> = Vec::with_capacity(m);
+    let mut next = 1;
+    for group in 0..m {
+        let size = n / m + usize::from(group < n % m);
+        groups.push((next..next + size).collect());
+        next += size;
+    }
+    Ok(Combinations::new((0..m).collect(), r)
+        .map(|chosen| {
+            let mut subset: Vec<usize> = chosen.iter().flat_map(|group| groups[*group].iter().cloned()).collect();
+            subset.truncate(k);
+            subset
+        })
+        .collect())
+}
+
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    (0..k).map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln()).sum()
+}
+
+///Number of random k-subsets needed so that, except with probability 2^-security_bits,
+///every set of t corrupted parties misses at least one subset.
+///Union bound over all C(n,t) corruption sets: C(n,t) * (1 - C(n-t,k)/C(n,k))^m <= 2^-security_bits.
+///The bound covers every corruption set at once, so it also holds when the adversary sees the family first.
+pub fn sampled_family_size(n: usize, k: usize, t: usize, security_bits: u32) -> Option<usize> {
+    if n < t + k {
+        return None;
+    }
+    let ln_p_honest = ln_binomial(n - t, k) - ln_binomial(n, k);
+    let ln_p_hit = (-ln_p_honest.exp()).ln_1p();
+    let ln_target = -(security_bits as f64) * 2f64.ln() - ln_binomial(n, t);
+    Some((ln_target / ln_p_hit).ceil().max(1.0) as usize)
+}
+
+///Result of analyzing a family against t corruptions
+#[derive(Clone, Debug)]
+pub struct FamilyAnalysis {
+    pub number_subsets: usize,
+    ///Probability that t uniformly random corrupted parties hit every subset
+    pub probability_adversary_controls_all: f64,
+    ///Whether the probability was computed by enumerating all corruption sets rather than sampling
+    pub exact: bool,
+    ///A set of at most t parties hitting every subset, if one was found
+    pub controlling_set: Option<Vec<usize>>,
+}
+
+//Bitset of the parties in a subset, one bit per party in words of 64
+fn mask(n: usize, subset: &[usize]) -> Vec<u64> {
+    let mut mask = vec![0u64; n.div_ceil(64)];
+    for party in subset {
+        mask[(party - 1) / 64] |= 1u64 << ((party - 1) % 64);
+    }
+    mask
+}
+
+fn contains(mask: &[u64], party: usize) -> bool {
+    mask[(party - 1) / 64] & (1u64 << ((party - 1) % 64)) != 0
+}
+
+fn intersects(a: &[u64], b: &[u64]) -> bool {
+    a.iter().zip(b).any(|(a, b)| a & b != 0)
+}
+
+fn controls_all(corruption_mask: &[u64], subset_masks: &[Vec<u64>]) -> bool {
+    subset_masks.iter().all(|subset_mask| intersects(subset_mask, corruption_mask))
+}
+
+//Greedily picks the party in most not-yet-hit subsets, which finds small controlling sets fast
+fn greedy_controlling_set(n: usize, t: usize, subset_masks: &[Vec<u64>]) -> Option<Vec<usize>> {
+    let mut remaining: Vec<&Vec<u64>> = subset_masks.iter().collect();
+    let mut chosen: Vec<usize> = Vec::new();
+    while !remaining.is_empty() && chosen.len() < t {
+        let best = (1..=n).max_by_key(|&party| remaining.iter().filter(|mask| contains(mask, party)).count())?;
+        chosen.push(best);
+        remaining.retain(|mask| !contains(mask, best));
+    }
+    if remaining.is_empty() {
+        chosen.sort();
+        Some(chosen)
+    } else {
+        None
+    }
+}
+
+///Computes how likely an adversary corrupting t of the n parties controls every subset of the family.
+///Corruption sets are enumerated when there are at most EXACT_ANALYSIS_LIMIT of them, otherwise `samples` are drawn.
+pub fn analyze(subsets: &[Vec<usize>], n: usize, t: usize, samples: usize, seed: u64) -> FamilyAnalysis {
+    let subset_masks: Vec<Vec<u64>> = subsets.iter().map(|subset| mask(n, subset)).collect();
+
+    //A count too large for a usize is above the limit as well
+    if let Some(number_corruption_sets) = binomial(n, t).filter(|count| *count <= EXACT_ANALYSIS_LIMIT) {
+        let mut controlling = 0;
+        let mut controlling_set: Option<Vec<usize>> = None;
+        for corruption_set in Combinations::new((1..=n).collect(), t) {
+            if controls_all(&mask(n, &corruption_set), &subset_masks) {
+                controlling += 1;
+                controlling_set.get_or_insert(corruption_set);
+            }
+        }
+        return FamilyAnalysis {
+            number_subsets: subsets.len(),
+            probability_adversary_controls_all: controlling as f64 / number_corruption_sets as f64,
+            exact: true,
+            controlling_set: controlling_set,
+        };
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut controlling = 0;
+    let mut controlling_set: Option<Vec<usize>> = None;
+    for _ in 0..samples {
+        let corruption_set = random_subset(n, t, &mut rng);
+        if controls_all(&mask(n, &corruption_set), &subset_masks) {
+            controlling += 1;
+            controlling_set.get_or_insert(corruption_set);
+        }
+    }
+    //A sampled controlling set is kept as found; otherwise the greedy search looks for one the samples missed
+    let controlling_set = controlling_set
+        .map(|mut corruption_set| {
+            corruption_set.sort();
+            corruption_set
+        })
+        .or_else(|| greedy_controlling_set(n, t, &subset_masks));
+    FamilyAnalysis {
+        number_subsets: subsets.len(),
+        probability_adversary_controls_all: controlling as f64 / samples.max(1) as f64,
+        exact: false,
+        controlling_set: controlling_set,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subsets_of(family: &SubsetFamily) -> Vec<Vec<usize>> {
+        match family {
+            SubsetFamily::Explicit(subsets) => subsets.as_ref().clone(),
+            SubsetFamily::Complete => panic!("covering designs are explicit"),
+        }
+    }
+
+    //No set of t parties hits every subset, checked over all t-sets
+    fn survives_every_corruption(subsets: &[Vec<usize>], n: usize, t: usize) -> bool {
+        let analysis = analyze(subsets, n, t, 0, 0);
+        assert!(analysis.exact);
+        analysis.probability_adversary_controls_all == 0.0 && analysis.controlling_set.is_none()
+    }
+
+    #[test]
+    fn greedy_covering_designs_survive_every_corruption_set() {
+        for (n, k, t) in [(4, 3, 1), (7, 5, 2), (10, 3, 4), (12, 4, 5), (9, 2, 6)] {
+            let subsets = subsets_of(&SubsetFamily::covering_design(n, k, t).unwrap());
+            assert!(subsets.iter().all(|subset| subset.len() == k && subset.windows(2).all(|pair| pair[0] < pair[1])));
+            assert!(survives_every_corruption(&subsets, n, t), "n={} k={} t={}", n, k, t);
+        }
+        //With n = 3t+1 and k = 2t+1 the complements are the t-sets themselves, so only the complete family covers them
+        assert_eq!(subsets_of(&SubsetFamily::covering_design(7, 5, 2).unwrap()).len(), binomial(7, 5).unwrap());
+        assert!(SubsetFamily::covering_design(4, 3, 2).is_err());
+    }
+
+    #[test]
+    fn grouped_covers_survive_every_corruption_set() {
+        for (n, k, t) in [(12, 4, 4), (13, 3, 5), (10, 5, 3), (16, 2, 9)] {
+            let subsets = grouped_cover(n, k, t).unwrap();
+            assert!(subsets.iter().all(|subset| subset.len() == k && subset.windows(2).all(|pair| pair[0] < pair[1])));
+            assert!(survives_every_corruption(&subsets, n, t), "n={} k={} t={}", n, k, t);
+        }
+    }
+
+    #[test]
+    fn covering_designs_scale_past_the_exact_limit() {
+        //C(60, 24) corruption sets are far too many to enumerate; 27 groups of two or three parties are not
+        let (n, k, t) = (60, 6, 24);
+        assert!(binomial(n, t).is_none_or(|count| count > EXACT_ANALYSIS_LIMIT));
+        let subsets = subsets_of(&SubsetFamily::covering_design(n, k, t).unwrap());
+        assert_eq!(subsets.len(), binomial(27, 3).unwrap());
+        let analysis = analyze(&subsets, n, t, 1000, 7);
+        assert!(!analysis.exact);
+        assert_eq!(analysis.probability_adversary_controls_all, 0.0);
+        assert!(analysis.controlling_set.is_none());
+    }
+
+    #[test]
+    fn sampled_families_are_reproducible_sorted_subsets() {
+        let family = subsets_of(&SubsetFamily::sampled(20, 7, 50, 3));
+        assert_eq!(family.len(), 50);
+        assert!(family.iter().all(|subset| subset.len() == 7 && subset.windows(2).all(|pair| pair[0] < pair[1]) && subset[0] >= 1 && subset[6] <= 20));
+        assert_eq!(family, subsets_of(&SubsetFamily::sampled(20, 7, 50, 3)));
+        assert_ne!(family, subsets_of(&SubsetFamily::sampled(20, 7, 50, 4)));
+    }
+
+    #[test]
+    fn sampled_family_size_grows_with_security() {
+        let sizes: Vec<usize> = (0..=128).map(|bits| sampled_family_size(31, 11, 10, bits).unwrap()).collect();
+        assert!(sizes.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(sizes[0] < sizes[128]);
+        assert_eq!(sampled_family_size(10, 5, 6, 40), None);
+    }
+
+    #[test]
+    fn analysis_of_a_tiny_family_is_exact() {
+        //Only party 1 is in both subsets, and it is one of the four single corruptions
+        let analysis = analyze(&[vec![1, 2], vec![1, 3]], 4, 1, 0, 0);
+        assert!(analysis.exact);
+        assert_eq!(analysis.number_subsets, 2);
+        assert_eq!(analysis.probability_adversary_controls_all, 0.25);
+        assert_eq!(analysis.controlling_set, Some(vec![1]));
+    }
+}
@@ -0,0 +1,187 @@
+//Combinatorial number system over the k-subsets of {1, ..., n}.
+//Subsets are always sorted and indexed by their position in lexicographic order,
+//which is the order in which the old recursive enumeration produced them.
+
+///Binomial coefficient C(n, k), 0 if k > n, or None if it does not fit in a usize
+pub fn binomial(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
+    for i in 0..k {
+        result = result.checked_mul((n - i) as u128)? / (i + 1) as u128;
+    }
+    usize::try_from(result).ok()
+}
+
+///Lexicographic index of a sorted k-subset of {1, ..., n}; C(n, k) must fit in a usize
+pub fn rank(n: usize, subset: &[usize]) -> usize {
+    let k = subset.len();
+    let mut index = 0;
+    let mut previous = 0;
+    for (position, &element) in subset.iter().enumerate() {
+        //Count all subsets that agree on the prefix but have a smaller element at this position
+        for smaller in previous + 1..element {
+            index += binomial(n - smaller, k - position - 1).expect("every term of the index is below C(n, k)");
+        }
+        previous = element;
+    }
+    index
+}
+
+///Sorted k-subset of {1, ..., n} with the given lexicographic index
+pub fn unrank(n: usize, k: usize, mut index: usize) -> Vec<usize> {
+    let mut subset: Vec<usize> = Vec::with_capacity(k);
+    let mut candidate = 1;
+    for position in 0..k {
+        loop {
+            //A count too large for a usize is larger than any index
+            let count = binomial(n - candidate, k - position - 1).unwrap_or(usize::MAX);
+            if index < count {
+                break;
+            }
+            index -= count;
+            candidate += 1;
+        }
+        subset.push(candidate);
+        candidate += 1;
+    }
+    subset
+}
+
+///Lazily enumerates the k-element combinations of a pool in lexicographic order
+pub struct Combinations {
+    pool: Vec<usize>,
+    positions: Vec<usize>,
+    done: bool,
+}
+
+impl Combinations {
+    pub fn new(pool: Vec<usize>, k: usize) -> Combinations {
+        let done = k > pool.len();
+        Combinations { pool: pool, positions: (0..k).collect(), done: done }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        let current: Vec<usize> = self.positions.iter().map(|&p| self.pool[p]).collect();
+
+        //Advance to the next combination: bump the rightmost position that still has room
+        let k = self.positions.len();
+        let len = self.pool.len();
+        match (0..k).rev().find(|&i| self.positions[i] < len - k + i) {
+            Some(i) => {
+                self.positions[i] += 1;
+                for j in i + 1..k {
+                    self.positions[j] = self.positions[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(current)
+    }
+}
+
+///All (index, subset) pairs whose smallest element is `leader`.
+///In lexicographic order these form one contiguous block of indices.
+pub fn leader_subsets(n: usize, k: usize, leader: usize) -> impl Iterator<Item = (usize, Vec<usize>)> {
+    let first_index = if k > 0 && leader + k - 1 <= n {
+        rank(n, &(leader..leader + k).collect::<Vec<usize>>())
+    } else {
+        0
+    };
+    let pool: Vec<usize> = (leader + 1..=n).collect();
+    let tails = if k > 0 { Combinations::new(pool, k - 1) } else { Combinations::new(Vec::new(), 1) };
+
+    tails.enumerate().map(move |(offset, tail)| {
+        let mut subset = Vec::with_capacity(k);
+        subset.push(leader);
+        subset.extend(tail);
+        (first_index + offset, subset)
+    })
+}
+
+///All (index, subset) pairs that contain `party`
+pub fn participation_subsets(n: usize, k: usize, party: usize) -> impl Iterator<Item = (usize, Vec<usize>)> {
+    let pool: Vec<usize> = (1..=n).filter(|&i| i != party).collect();
+    let others = if k > 0 { Combinations::new(pool, k - 1) } else { Combinations::new(Vec::new(), 1) };
+
+    others.map(move |mut subset| {
+        let position = subset.partition_point(|&i| i < party);
+        subset.insert(position, party);
+        (rank(n, &subset), subset)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Every k-subset of {1, ..., n}, in lexicographic order
+    fn all_subsets(n: usize, k: usize) -> Vec<Vec<usize>> {
+        Combinations::new((1..=n).collect(), k).collect()
+    }
+
+    #[test]
+    fn rank_and_unrank_invert_each_other_on_every_subset() {
+        for n in 1..=8 {
+            for k in 0..=n {
+                let count = binomial(n, k).unwrap();
+                for index in 0..count {
+                    let subset = unrank(n, k, index);
+                    assert_eq!(subset.len(), k);
+                    assert!(subset.windows(2).all(|pair| pair[0] < pair[1]) && subset.iter().all(|i| (1..=n).contains(i)));
+                    assert_eq!(rank(n, &subset), index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn combinations_come_in_rank_order() {
+        for n in 1..=8 {
+            for k in 0..=n {
+                let subsets = all_subsets(n, k);
+                assert_eq!(subsets.len(), binomial(n, k).unwrap());
+                for (index, subset) in subsets.iter().enumerate() {
+                    assert_eq!(rank(n, subset), index);
+                }
+            }
+        }
+        assert_eq!(Combinations::new(vec![1, 2], 3).count(), 0);
+    }
+
+    #[test]
+    fn binomial_reports_overflow() {
+        assert_eq!(binomial(5, 2), Some(10));
+        assert_eq!(binomial(3, 4), Some(0));
+        assert_eq!(binomial(64, 32), Some(1832624140942590534));
+        assert_eq!(binomial(200, 100), None);
+        assert_eq!(binomial(usize::MAX, 3), None);
+    }
+
+    #[test]
+    fn leader_and_participation_subsets_match_a_brute_force_filter() {
+        for n in 1..=7 {
+            for k in 1..=n {
+                let indexed: Vec<(usize, Vec<usize>)> = all_subsets(n, k).into_iter().enumerate().collect();
+                for party in 1..=n {
+                    let led: Vec<(usize, Vec<usize>)> = indexed.iter().filter(|(_, subset)| subset[0] == party).cloned().collect();
+                    assert_eq!(leader_subsets(n, k, party).collect::<Vec<_>>(), led);
+
+                    let mut joined: Vec<(usize, Vec<usize>)> = participation_subsets(n, k, party).collect();
+                    joined.sort();
+                    let expected: Vec<(usize, Vec<usize>)> = indexed.iter().filter(|(_, subset)| subset.contains(&party)).cloned().collect();
+                    assert_eq!(joined, expected);
+                }
+            }
+        }
+    }
+}
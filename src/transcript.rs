@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use ed25519_dalek::PublicKey;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::metrics::MessageKind;
+use crate::rand_extr::LeaderRule;
+
+//A transcript is what an observer of every channel saw during a run. Private messages are kept in the clear
+//so that each check the receiving role made can be made again offline; a transcript therefore reveals the
+//dealt secrets and is meant to be published once they have been opened anyway.
+
+///Protocol a transcript was recorded from, with the public parameters needed to replay it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Protocol {
+    ///A single VSS dealing of `packing` secrets, recorded as session 0
+    Vss { t: u64, n: u64, packing: u64 },
+    ///The VSS-based beacon, one session per dealer
+    Beacon { t: u64, n: u64, packing: u64 },
+    ///The combinatorial extractor, recorded as session 0; `subsets` is None for the complete family
+    Extractor { n: usize, k: usize, t: usize, leader_rule: LeaderRule, subsets: Option<Vec<Vec<usize>>> },
+}
+
+///One message as it went over the wire
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TranscriptMessage {
+    pub session: u64,
+    pub round: u64,
+    pub kind: MessageKind,
+    pub sender: u64,
+    ///None for broadcasts
+    pub receiver: Option<u64>,
+    pub payload: Vec<u8>,
+}
+
+///Every message of a run together with the output it claims
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Transcript {
+    pub protocol: Protocol,
+    ///Signing keys of each session's roles, by pki index
+    pub keys: BTreeMap<u64, Vec<[u8; 32]>>,
+    pub messages: Vec<TranscriptMessage>,
+    ///Serialized output of the run
+    pub output: Vec<u8>,
+    //Session messages are currently recorded in
+    #[serde(skip)]
+    session: u64,
+}
+
+//All messages of a kind go out in the same round
+fn round_of(kind: MessageKind) -> u64 {
+    match kind {
+        MessageKind::DealerRow | MessageKind::DealerCommitment | MessageKind::LeaderValues => 1,
+        MessageKind::DoublySignedSubshare | MessageKind::ForwardedValues => 2,
+        MessageKind::TriplySignedSubshares | MessageKind::VerifiedValues => 3,
+        MessageKind::Publication | MessageKind::PublishedValues | MessageKind::Syndromes => 4,
+    }
+}
+
+impl Transcript {
+    pub fn new(protocol: Protocol) -> Transcript {
+        Transcript { protocol: protocol, keys: BTreeMap::new(), messages: Vec::new(), output: Vec::new(), session: 0 }
+    }
+
+    ///Records the following messages and keys in `session`
+    pub fn start_session(&mut self, session: u64) {
+        self.session = session;
+    }
+
+    pub fn set_keys(&mut self, pki: &[PublicKey]) {
+        self.keys.insert(self.session, pki.iter().map(|pk| pk.to_bytes()).collect());
+    }
+
+    pub fn record(&mut self, kind: MessageKind, sender: u64, receiver: Option<u64>, payload: Vec<u8>) {
+        self.messages.push(TranscriptMessage { session: self.session, round: round_of(kind), kind: kind, sender: sender, receiver: receiver, payload: payload });
+    }
+
+    ///Payload of the first such message; as in the protocols, later ones do not count
+    pub fn message(&self, session: u64, kind: MessageKind, sender: u64, receiver: Option<u64>) -> Option<&[u8]> {
+        self.messages.iter()
+            .find(|message| message.session == session && message.kind == kind && message.sender == sender && message.receiver == receiver)
+            .map(|message| &message.payload[..])
+    }
+
+    pub fn keys_of(&self, session: u64) -> Result<Vec<PublicKey>> {
+        let keys = self.keys.get(&session).ok_or_else(|| Error::Transcript(format!("no role keys for session {}", session)))?;
+        keys.iter()
+            .map(|bytes| PublicKey::from_bytes(bytes).map_err(|_| Error::Transcript(format!("invalid role key in session {}", session))))
+            .collect()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let bytes = bincode::serialize(self).map_err(|error| Error::Transcript(error.to_string()))?;
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Transcript> {
+        let bytes = fs::read(path)?;
+        bincode::deserialize(&bytes).map_err(|error| Error::Transcript(error.to_string()))
+    }
+}
+
+///A recorded message that is not what the sender's role would have sent had it been honest
+#[derive(Clone, Debug, Serialize)]
+pub struct Deviation {
+    pub session: u64,
+    pub round: u64,
+    pub party: u64,
+    pub reason: String,
+}
+
+impl Deviation {
+    pub fn new(session: u64, kind: MessageKind, party: u64, reason: String) -> Deviation {
+        Deviation { session: session, round: round_of(kind), party: party, reason: reason }
+    }
+}
+
+///Result of replaying a transcript: the output the honest checks lead to and every deviation found on the way
+pub struct Verification<O> {
+    pub output: O,
+    pub claimed: O,
+    pub deviations: Vec<Deviation>,
+}
+
+impl<O: PartialEq> Verification<O> {
+    pub fn output_matches(&self) -> bool {
+        self.output == self.claimed
+    }
+}
+
+impl<O> Verification<O> {
+    ///The same verification with both outputs converted, e.g. to print them
+    pub fn map<P>(self, convert: impl Fn(O) -> P) -> Verification<P> {
+        Verification { output: convert(self.output), claimed: convert(self.claimed), deviations: self.deviations }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fq;
+    use ark_serialize::CanonicalSerialize;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::adversary::Honest;
+    use crate::rand_extr::{ExtractorParams, RandomnessExtractor};
+    use crate::role_assignment::generate_keypair;
+    use crate::vss::{PubParams, VSS};
+
+    use super::*;
+
+    #[test]
+    fn a_saved_transcript_loads_with_its_keys_and_first_messages() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let keys: Vec<PublicKey> = (0..3).map(|_| generate_keypair(&mut rng).public).collect();
+        let mut transcript = Transcript::new(Protocol::Vss { t: 1, n: 2, packing: 1 });
+        transcript.start_session(0);
+        transcript.set_keys(&keys);
+        transcript.record(MessageKind::DealerRow, 0, Some(1), vec![1]);
+        transcript.record(MessageKind::DealerRow, 0, Some(1), vec![2]);
+        transcript.record(MessageKind::Publication, 3, None, vec![3]);
+
+        let path = std::env::temp_dir().join(format!("yosowcc-transcript-test-{}.bin", std::process::id()));
+        transcript.save(&path).unwrap();
+        let loaded = Transcript::load(&path).unwrap();
+        assert_eq!(loaded.message(0, MessageKind::DealerRow, 0, Some(1)), Some(&[1u8][..]));
+        assert_eq!(loaded.message(0, MessageKind::Publication, 3, None), Some(&[3u8][..]));
+        assert_eq!(loaded.message(0, MessageKind::Publication, 3, Some(1)), None);
+        assert_eq!(loaded.messages[2].round, 4);
+        assert_eq!(loaded.keys_of(0).unwrap(), keys);
+        assert!(matches!(loaded.keys_of(1), Err(Error::Transcript(_))));
+
+        fs::write(&path, [0xff; 5]).unwrap();
+        assert!(matches!(Transcript::load(&path), Err(Error::Transcript(_))));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn a_replayed_vss_flags_a_changed_claim_and_a_changed_message() {
+        let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(5u64)], pp: PubParams::with_size_and_seed(1, 4, 9, 3).unwrap(), execution_leaks: false, seed: 3 };
+        let (_, mut transcript) = vss.execute_recorded(&mut Honest).unwrap();
+        let verification = VSS::<Fq>::verify_transcript(&transcript).unwrap();
+        assert!(verification.output_matches() && verification.deviations.is_empty());
+        assert_eq!(verification.output, vec![Fq::from(5u64)]);
+
+        let mut claimed = Vec::new();
+        vec![Fq::from(6u64)].serialize_compressed(&mut claimed).unwrap();
+        let honest_output = std::mem::replace(&mut transcript.output, claimed);
+        let verification = VSS::<Fq>::verify_transcript(&transcript).unwrap();
+        assert!(!verification.output_matches());
+        assert!(verification.deviations.is_empty());
+
+        //Receiver 2's doubly signed subshares to receiver 3, with a byte of the payload changed
+        transcript.output = honest_output;
+        let message = transcript.messages.iter_mut()
+            .find(|message| message.kind == MessageKind::DoublySignedSubshare && message.sender == 2 && message.receiver == Some(3))
+            .unwrap();
+        message.payload[0] ^= 1;
+        let verification = VSS::<Fq>::verify_transcript(&transcript).unwrap();
+        assert!(verification.output_matches());
+        //Receiver 2 is blamed for the round it sent in; receiver 3 recorded forwarding a row it could not have verified
+        assert!(verification.deviations.iter().any(|deviation| deviation.party == 2 && deviation.round == 2));
+        assert!(verification.deviations.iter().all(|deviation| (deviation.party, deviation.round) == (2, 2) || (deviation.party, deviation.round) == (3, 3)));
+    }
+
+    #[test]
+    fn a_replayed_extractor_flags_a_changed_coin() {
+        let rand_extr = RandomnessExtractor { params: ExtractorParams::new(1), seed: Some(4) };
+        let (outcome, mut transcript) = rand_extr.execute_recorded(&mut Honest).unwrap();
+        assert!(RandomnessExtractor::verify_transcript(&transcript).unwrap().output_matches());
+        transcript.output = (1 - outcome.coin).to_le_bytes().to_vec();
+        let verification = RandomnessExtractor::verify_transcript(&transcript).unwrap();
+        assert!(!verification.output_matches());
+        assert_eq!(verification.output, outcome.coin);
+    }
+}
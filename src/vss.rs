@@ -1,20 +1,29 @@
-use std::{collections::BTreeMap};
-use ark_ff::BigInteger;
+use std::collections::{BTreeMap, BTreeSet};
 use ark_ff::{Field};
-use ark_std::test_rng;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 
 //use pqcrypto_sphincsplus::sphincssha256128fsimple::*;
 use crate::polynomials::Poly;
 use crate::polynomials::SymBVPoly;
+use crate::party::{evaluation_points_of, secret_points};
 use crate::communication::*;
-use std::mem::size_of_val;
+use crate::metrics::{CostReport, MessageKind, Metrics, Role, RoleCost};
+use crate::leakage::{LeakageAdversary, LeakageReport};
+use crate::adversary::{Adversary, DealingView, Honest};
+use crate::error::{Error, Result};
+use crate::role_assignment::{NominatingCommittee, Party, RoleAssignment, RoleKeys};
+use crate::transcript::{Deviation, Protocol, Transcript, Verification};
+use crate::merkle::{root_message, MerklePath, MerkleTree, VerifiedRoots};
+use std::sync::Arc;
 use std::time::{SystemTime, Duration};
 use ed25519_dalek::*;
-use rand::rngs::OsRng;
 
-use ark_std::rand::prelude::StdRng;
+use rand::rngs::StdRng;
 
-use ark_std::rand::SeedableRng;
+use rand::{Rng, SeedableRng};
+use ark_std::{cfg_into_iter, cfg_iter};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 pub struct PubParams {
     //Number of potentially adversarial parties
     pub t: u64,
@@ -23,505 +32,1489 @@ pub struct PubParams {
     //Total umber of parties
     pub n_parties_total: u64,
     //pub sig_pp: Parameters<Edwards, Blake2s>
+    //Published role keys of the dealing's committee: role 0 is the dealer, role i receiver i
+    pub roles: RoleAssignment,
+    //How the dealer and receivers endorse the subshares they send
+    pub authentication: Authentication,
+    //Number of secrets packed into one dealing
+    pub packing: u64,
+    //Role the dealer's keys were nominated for: 0, or its receiver index in the previous committee when it reshares
+    dealer_role: u64,
+    //Seed the members of the committee nominating the next committee draw their contributions from
+    nomination_seed: u64,
+    //The simulated parties the roles were assigned to; the protocol itself only sees `roles`.
+    //Committees that pass secrets on to each other are drawn from the same parties.
+    parties: Arc<Vec<Party>>
+}
+
+impl PubParams {
+    ///The standard setting: 3t+1 receivers and 5t+4 parties overall
+    pub fn new(t: u64) -> Result<PubParams> {
+        let (n, n_parties_total) = PubParams::standard_size(t);
+        PubParams::with_size(t, n, n_parties_total)
+    }
+
+    ///Number of receivers and of parties overall in the standard setting
+    pub fn standard_size(t: u64) -> (u64, u64) {
+        PubParams::packed_size(t, 1)
+    }
+
+    ///The standard setting for `packing` secrets per dealing: every further secret takes one more receiver
+    pub fn packed(t: u64, packing: u64) -> Result<PubParams> {
+        let (n, n_parties_total) = PubParams::packed_size(t, packing);
+        Ok(PubParams::with_size(t, n, n_parties_total)?.with_packing(packing))
+    }
+
+    pub fn packed_size(t: u64, packing: u64) -> (u64, u64) {
+        (3*t + packing, 5*t + 3 + packing)
+    }
+
+    ///A fresh committee of a dealer and n receivers, nominated among n_parties_total parties by a committee of t+1
+    pub fn with_size(t: u64, n: u64, n_parties_total: u64) -> Result<PubParams> {
+        PubParams::with_size_and_seed(t, n, n_parties_total, rand::random())
+    }
+
+    ///As `with_size`, with the parties' long-term keys and the nominating members' contributions drawn from `seed`,
+    ///so that the same seed nominates the same role keys
+    pub fn with_size_and_seed(t: u64, n: u64, n_parties_total: u64, seed: u64) -> Result<PubParams> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let parties: Vec<Party> = (0..n_parties_total).map(|_| Party::generate(&mut rng)).collect();
+        let long_term_keys: Vec<EncryptionKey> = parties.iter().map(|party| party.long_term_key()).collect();
+        let roles = NominatingCommittee::generate(t + 1, &mut rng).nominate(n + 1, &long_term_keys)?;
+        Ok(PubParams { t: t, n: n, n_parties_total: n_parties_total, roles: roles, authentication: Authentication::Signatures, packing: 1, dealer_role: 0, nomination_seed: rng.gen(), parties: Arc::new(parties) })
+    }
+
+    ///A fresh committee of the same size and settings, nominated among the same parties
+    pub fn next_committee(&self) -> Result<PubParams> {
+        let mut rng = StdRng::seed_from_u64(self.nomination_seed);
+        let long_term_keys: Vec<EncryptionKey> = self.parties.iter().map(|party| party.long_term_key()).collect();
+        let roles = NominatingCommittee::generate(self.t + 1, &mut rng).nominate(self.n + 1, &long_term_keys)?;
+        Ok(PubParams { roles: roles, dealer_role: 0, nomination_seed: rng.gen(), parties: self.parties.clone(), ..*self })
+    }
+
+    ///Parameters of receiver `holder` of this committee dealing to the receivers of `next`: the dealer keeps the keys of its receiver role
+    pub fn dealing_to(&self, holder: u64, next: &PubParams) -> Result<PubParams> {
+        if !Arc::ptr_eq(&self.parties, &next.parties) {
+            return Err(Error::InvalidParams("the next committee was not nominated among the same parties".to_string()));
+        }
+        if holder == self.dealer_id() || holder > self.n {
+            return Err(Error::InvalidParams(format!("{} is not a receiver of the committee", holder)));
+        }
+        let mut roles = next.roles.clone();
+        roles.nominations.insert(next.dealer_id(), self.roles.nominations[&holder].clone());
+        Ok(PubParams { roles: roles, dealer_role: holder, parties: next.parties.clone(), ..*next })
+    }
+
+    ///The same committee endorsing subshares with `authentication`
+    pub fn with_authentication(mut self, authentication: Authentication) -> PubParams {
+        self.authentication = authentication;
+        self
+    }
+
+    ///The same committee sharing `packing` secrets per dealing
+    pub fn with_packing(mut self, packing: u64) -> PubParams {
+        self.packing = packing;
+        self
+    }
+
+    ///Degree of the sharing polynomial in each variable: t for privacy plus one for every further packed secret
+    pub fn degree(&self) -> u64 {
+        self.t + self.packing - 1
+    }
+
+    ///Secret keys of a role, taken from whichever party was nominated for it
+    pub fn claim_role(&self, role: u64) -> Result<RoleKeys> {
+        let nomination = self.roles.nominations.get(&role).ok_or_else(|| Error::InvalidParams(format!("role {} was never nominated", role)))?;
+        let nominated_as = if role == self.dealer_id() { self.dealer_role } else { role };
+        self.parties.iter()
+            .find_map(|party| party.open(nominated_as, nomination))
+            .ok_or_else(|| Error::InvalidParams(format!("no party holds role {}", role)))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.packing == 0 {
+            return Err(Error::InvalidParams("a dealing shares at least one secret".to_string()));
+        }
+        //The client needs degree+t+1 subshares per row and degree+1 rows out of the n receivers
+        if self.n < self.degree() + self.t + 1 {
+            return Err(Error::InvalidParams(format!("{} receivers cannot tolerate {} corruptions with {} packed secrets, at least {} are needed", self.n, self.t, self.packing, self.degree() + self.t + 1)));
+        }
+        //t+1 dealers each followed by n receivers, plus t+1 reconstructors
+        if self.n_parties_total < self.n + 2*self.t + 2 {
+            return Err(Error::InvalidParams(format!("{} parties in total are too few, at least {} are needed", self.n_parties_total, self.n + 2*self.t + 2)));
+        }
+        Ok(())
+    }
+
+    //Party ids in cost reports: the dealer is 0, receivers 1..=n as in pki, then the reconstructors and the client
+    pub fn dealer_id(&self) -> u64 {
+        0
+    }
+
+    pub fn reconstructor_id(&self, j: u64) -> u64 {
+        self.n + j
+    }
+
+    pub fn client_id(&self) -> u64 {
+        self.n + self.t + 2
+    }
 }
 
 pub struct VSS<F: Field> {
-    pub secret: F,
+    //One secret per packed slot
+    pub secrets: Vec<F>,
     pub pp: PubParams,
-    pub execution_leaks: bool
+    pub execution_leaks: bool,
+    //Seed for the dealer's sharing polynomial
+    pub seed: u64
 }
 
+///Per-party costs and reconstructed secret of one VSS run
+pub struct VssReport<F: Field> {
+    pub costs: CostReport,
+    pub secrets: Vec<F>,
+    //Whether the client verified t+1 rows
+    pub reconstructed: bool,
+    //Whether the reconstructed secrets are the ones that were dealt and, with execution leaks, no forgery got through and secrecy held
+    pub success: bool,
+    pub leakage: Option<LeakageReport>,
+    //Number of receivers that complained about the dealer
+    pub complaints: u64,
+    //Receivers accused of sending bad or no subshares, with their accusers
+    pub accusations: BTreeMap<u64, BTreeSet<u64>>
+}
+
+///What the sharing phase of one dealing leaves on the channels
+pub struct Sharing<F: Field> {
+    pub(crate) pki: Vec<PublicKey>,
+    shares_triple_signed: BTreeMap<u64, BTreeMap<u64, Subshare<F>>>,
+    //Values of the rows the receivers keep, entry k-1 at receiver k's point; a receiver that complained keeps none
+    pub(crate) rows: BTreeMap<u64, Vec<F>>,
+    //Number of receivers that complained about the dealing
+    pub complaints: u64,
+    pub accusations: BTreeMap<u64, BTreeSet<u64>>
+}
+
+///Coin from n parallel VSS dealings of random secrets: the output is the sum of the qualified dealers' secrets
 pub struct RandExtractorVSSBased<F: Field> {
-    pub secret: F,
     pub pp: PubParams,
-    pub execution_leaks: bool
+    pub execution_leaks: bool,
+    //Seed for the dealers' secrets and polynomials, fresh randomness if None
+    pub seed: Option<u64>,
+    _marker: std::marker::PhantomData<F>
+}
+
+///Output and per-dealing costs of one beacon run
+pub struct BeaconOutcome<F: Field> {
+    //One value per packed slot
+    pub output: Vec<F>,
+    //Dealers whose dealing entered the output
+    pub qualified: Vec<u64>,
+    //Costs of each dealing, indexed by dealer
+    pub dealings: BTreeMap<u64, CostReport>,
+    pub complaints: BTreeMap<u64, u64>,
+    //Every qualified dealing was reconstructed and, with execution leaks, no forgery got through and secrecy held
+    pub success: bool,
+    pub time: Duration
 }
 
 pub struct Dealer<'a, F: Field> {
     pub pp: &'a PubParams,
-    pub secret: F,
-    //pk: Option<PublicKey>,
-    //sk: Option<SecretKey>
-    keypair: Option<Keypair>
+    pub secrets: Vec<F>,
+    pub seed: u64,
+    keypair: Keypair
 }
 
-pub struct Resolver<'a, F: Field> {
-    pub pp: &'a PubParams,
-    pub secret: F,
-    pub bc: &'a BroadcastChannel,
-    pub pcs: &'a Vec<Vec<PrivateChannel>> 
+///Settles the complaints about parallel dealings once all their receivers have spoken
+pub struct Resolver {
+    pub t: u64,
 }
 
 pub struct Receiver<'a, F: Field> {
     pub id: u64,
     pub pp: &'a PubParams,
-    //pk: Option<PublicKey>,
-    //sk: Option<SecretKey>
-    keypair: Option<Keypair>,
-    share: BTreeMap<u64, Subshare<F>>
+    keypair: Keypair,
+    //Opens the row the dealer encrypts to this receiver
+    role_key: RoleKey,
+    share: BTreeMap<u64, Subshare<F>>,
+    //Inconsistencies that only the dealer can have caused
+    complaints: u64,
+    //Receivers whose subshares were missing or badly signed
+    accused: BTreeSet<u64>,
+    //Receivers a subshare has arrived from; later messages from them cannot lead to accusations
+    heard_from: BTreeSet<u64>,
+    //Receivers that sent a properly endorsed subshare disagreeing with our row, and those that sent one agreeing with it
+    disputed: BTreeSet<u64>,
+    agreed: BTreeSet<u64>,
+    roots: VerifiedRoots
 }
 
-//impl<'a, F: Field> Receiver<'a, F> {
-//    fn modify_share(&mut self, share: &BTreeMap<u64, Subshare<F>>) {
-//        self.share = share.clone();
-//    }
-//}
-
 pub struct Reconstructor<'a, F: Field> {
+    pub id: u64,
     pub pp: &'a PubParams,
-    _marker: std::marker::PhantomData<F>,
+    received: BTreeMap<u64, BTreeMap<u64, Subshare<F>>>
 }
 
-pub struct Client<'a, F: Field> {
-    pub pp: &'a PubParams,
+///Needs nothing but the public parameters, so anybody can play the client
+pub struct Client<F: Field> {
+    pub t: u64,
+    pub n: u64,
+    //Number of secrets packed into the dealing
+    pub packing: u64,
+    roots: VerifiedRoots,
     _marker: std::marker::PhantomData<F>
 }
 
-///Subshare consists of value, and a subset of three signarures on this value
+///How a party endorses the subshares it sends
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Authentication {
+    ///One signature per subshare
+    Signatures,
+    ///One signature on the root of a Merkle tree over everything the party sends in a round:
+    ///the dealer commits to its whole matrix, a receiver to each batch of subshares it countersigns
+    Merkle,
+}
+
+///A party's endorsement of a subshare value: a signature on the value, or a signature on a Merkle root and the path from the value up to it
+#[derive(Clone, Debug, PartialEq)]
+pub enum Endorsement {
+    Signature(Signature),
+    Path { root_signature: Signature, path: MerklePath },
+}
+
+impl Endorsement {
+    pub fn serialized_size(&self) -> u64 {
+        match self {
+            Endorsement::Signature(_) => SIGNATURE_LENGTH as u64,
+            Endorsement::Path { path, .. } => SIGNATURE_LENGTH as u64 + path.serialized_size(),
+        }
+    }
+}
+
+///Subshare consists of value, and a subset of three endorsements on this value
 #[derive(Clone)]
 pub struct Subshare<F: Field> {
-    value: F,
-    dealer_signature: Option<Signature>,
-    p_i_signature: Option<Signature>,
-    p_j_signature: Option<Signature>
+    pub(crate) value: F,
+    pub(crate) dealer_signature: Option<Endorsement>,
+    pub(crate) p_i_signature: Option<Endorsement>,
+    pub(crate) p_j_signature: Option<Endorsement>
 }
 
 impl<F: Field> Subshare<F> {
-    fn modify_dealer_signature(&mut self, signature: &Signature) {
-        self.dealer_signature = Some(signature.clone());
+    ///Bytes on the wire: the uncompressed value and every endorsement attached so far.
+    ///Indices are implied by the position of the subshare in its message.
+    pub fn serialized_size(&self) -> u64 {
+        let endorsements: u64 = [&self.dealer_signature, &self.p_i_signature, &self.p_j_signature].iter()
+                                    .filter_map(|endorsement| endorsement.as_ref())
+                                    .map(|endorsement| endorsement.serialized_size())
+                                    .sum();
+        self.value.uncompressed_size() as u64 + endorsements
     }
-    fn modify_p_i_signature(&mut self, signature: &Signature) {
-        self.p_i_signature = Some(signature.clone());
+}
+
+fn split<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if bytes.len() < len {
+        return None;
     }
-    fn modify_p_j_signature(&mut self, signature: &Signature) {
-        self.p_j_signature = Some(signature.clone());
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Some(head)
+}
+
+//Appends an optional endorsement as a flag (0 none, 1 signature, 2 path) followed by its bytes;
+//a path is the root signature, the leaf index as u64 LE, the number of siblings as one byte and the siblings
+fn write_endorsement(bytes: &mut Vec<u8>, endorsement: &Option<Endorsement>) {
+    match endorsement {
+        Some(Endorsement::Signature(signature)) => {
+            bytes.push(1);
+            bytes.extend_from_slice(&signature.to_bytes());
+        },
+        Some(Endorsement::Path { root_signature, path }) => {
+            bytes.push(2);
+            bytes.extend_from_slice(&root_signature.to_bytes());
+            bytes.extend_from_slice(&path.index.to_le_bytes());
+            bytes.push(path.siblings.len() as u8);
+            for sibling in &path.siblings {
+                bytes.extend_from_slice(sibling);
+            }
+        },
+        None => bytes.push(0),
     }
 }
 
+fn read_endorsement(bytes: &mut &[u8]) -> Option<Option<Endorsement>> {
+    let flag = split(bytes, 1)?[0];
+    if flag == 0 {
+        return Some(None);
+    }
+    let signature = Signature::from_bytes(split(bytes, SIGNATURE_LENGTH)?).ok()?;
+    match flag {
+        1 => Some(Some(Endorsement::Signature(signature))),
+        2 => {
+            let index = u64::from_le_bytes(split(bytes, 8)?.try_into().ok()?);
+            let count = split(bytes, 1)?[0] as usize;
+            let siblings = (0..count).map(|_| split(bytes, 32)?.try_into().ok()).collect::<Option<Vec<[u8; 32]>>>()?;
+            Some(Some(Endorsement::Path { root_signature: signature, path: MerklePath { index: index, siblings: siblings } }))
+        },
+        _ => None,
+    }
+}
+
+//A row as a private-channel payload: per subshare its index, the uncompressed value and the endorsements
+fn encode_row<F: Field>(row: &BTreeMap<u64, Subshare<F>>) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for (k, subshare) in row {
+        bytes.extend_from_slice(&k.to_le_bytes());
+        subshare.value.serialize_uncompressed(&mut bytes)?;
+        for endorsement in [&subshare.dealer_signature, &subshare.p_i_signature, &subshare.p_j_signature] {
+            write_endorsement(&mut bytes, endorsement);
+        }
+    }
+    Ok(bytes)
+}
+
+//Inverse of encode_row; anything that does not parse is a malformed message from the dealer
+fn decode_row<F: Field>(mut bytes: &[u8]) -> Result<BTreeMap<u64, Subshare<F>>> {
+    let malformed = || Error::MalformedMessage { from: 0, reason: "row does not parse".to_string() };
+    let mut row = BTreeMap::new();
+    while !bytes.is_empty() {
+        let k = split(&mut bytes, 8).ok_or_else(malformed)?;
+        let value = F::deserialize_uncompressed(&mut bytes).map_err(|_| malformed())?;
+        let dealer_signature = read_endorsement(&mut bytes).ok_or_else(malformed)?;
+        let p_i_signature = read_endorsement(&mut bytes).ok_or_else(malformed)?;
+        let p_j_signature = read_endorsement(&mut bytes).ok_or_else(malformed)?;
+        let k = u64::from_le_bytes(k.try_into().unwrap_or_default());
+        row.insert(k, Subshare { value: value, dealer_signature: dealer_signature, p_i_signature: p_i_signature, p_j_signature: p_j_signature });
+    }
+    Ok(row)
+}
+
+//Bytes the subshare at (i, j) of the dealing by `dealer_pk` is endorsed as, by the dealer and by p_i and p_j alike.
+//The entry is named by its unordered pair, since S(x_i, x_j) = S(x_j, x_i), and the dealing by its dealer's key,
+//so an endorsement or Merkle leaf holds for one position of one dealing only.
+pub(crate) fn signed_bytes<F: Field>(value: &F, dealer_pk: &PublicKey, i: u64, j: u64) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(dealer_pk.as_bytes());
+    bytes.extend_from_slice(&i.min(j).to_le_bytes());
+    bytes.extend_from_slice(&i.max(j).to_le_bytes());
+    value.serialize_uncompressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+//Endorsements on each of `leaves`, in order. With Merkle authentication a single signature covers them all.
+fn endorse(leaves: &[Vec<u8>], keypair: &Keypair, authentication: Authentication) -> Result<Vec<Endorsement>> {
+    match authentication {
+        Authentication::Signatures => Ok(cfg_iter!(leaves).map(|leaf| Endorsement::Signature(keypair.sign(leaf))).collect()),
+        Authentication::Merkle => {
+            let tree = MerkleTree::new(leaves);
+            let root_signature = keypair.sign(&root_message(&tree.root()));
+            Ok((0..leaves.len()).map(|index| Endorsement::Path { root_signature: root_signature, path: tree.path(index) }).collect())
+        },
+    }
+}
+
+//Checks the endorsement the party with pki index `signer` put on `bytes`; roots already in `roots` are not checked again
+fn verify_endorsement(bytes: &[u8], endorsement: &Option<Endorsement>, pk: &PublicKey, signer: u64, roots: &VerifiedRoots) -> Result<()> {
+    let verified = match endorsement.as_ref().ok_or(Error::MissingSignature { signer: signer })? {
+        Endorsement::Signature(signature) => pk.verify(bytes, signature).is_ok(),
+        Endorsement::Path { root_signature, path } => roots.verify(pk, &path.root(bytes), root_signature),
+    };
+    if !verified {
+        return Err(Error::BadSignature { signer: signer });
+    }
+    Ok(())
+}
+
+//Checks the endorsements of the dealer, p_i and p_j on the subshare at position (i, j), i <= j
+fn verify_triply_endorsed<F: Field>(share: &Subshare<F>, i: u64, j: u64, pki: &[PublicKey], roots: &VerifiedRoots) -> Result<()> {
+    let share_bytes = signed_bytes(&share.value, &pki[0], i, j)?;
+    verify_endorsement(&share_bytes, &share.dealer_signature, &pki[0], 0, roots)?;
+    verify_endorsement(&share_bytes, &share.p_i_signature, &pki[i as usize], i, roots)?;
+    verify_endorsement(&share_bytes, &share.p_j_signature, &pki[j as usize], j, roots)
+}
+
+//Everything receiver i complains about in the row it got from the dealer: each entry must carry the dealer's
+//endorsement, and the row must have one entry per receiver and lie on a polynomial of degree `degree`
+fn row_problems<F: Field>(i: u64, row: &BTreeMap<u64, Subshare<F>>, dealer_pk: &PublicKey, points: &[F], n: u64, degree: u64) -> Vec<Error> {
+    let mut problems = Vec::new();
+    let roots = VerifiedRoots::default();
+    for (k, subshare) in row {
+        if let Err(error) = signed_bytes(&subshare.value, dealer_pk, i, *k).and_then(|share_bytes| verify_endorsement(&share_bytes, &subshare.dealer_signature, dealer_pk, 0, &roots)) {
+            problems.push(error);
+        }
+    }
+
+    let indices: Vec<u64> = row.keys().cloned().collect();
+    if indices != (1..=n).collect::<Vec<u64>>() {
+        //The row is incomplete, missing altogether or has entries for receivers that do not exist
+        problems.push(Error::MalformedMessage { from: 0, reason: "row does not have one entry per receiver".to_string() });
+    } else {
+        let x_vals: Vec<F> = indices.iter().map(|k| points[(k - 1) as usize]).collect();
+        let y_vals: Vec<F> = row.values().map(|signed_share| signed_share.value).collect();
+        match Poly::evals_to_coeffs(&x_vals, &y_vals) {
+            Ok(unipoly) if unipoly.degree <= degree => {},
+            Ok(unipoly) => problems.push(Error::DegreeTooHigh { degree: unipoly.degree, max: degree }),
+            Err(error) => problems.push(error.into()),
+        }
+    }
+    problems
+}
+
+//Checks both endorsements on a subshare from p_from to receiver `to` and that it matches the receiver's own row
+fn check_subshare<F: Field>(own_row: &BTreeMap<u64, Subshare<F>>, from: u64, to: u64, share: &Subshare<F>, dealer_pk: &PublicKey, pk_i: &PublicKey, roots: &VerifiedRoots) -> Result<()> {
+    let share_bytes = signed_bytes(&share.value, dealer_pk, from, to)?;
+    verify_endorsement(&share_bytes, &share.dealer_signature, dealer_pk, 0, roots)?;
+    verify_endorsement(&share_bytes, &share.p_i_signature, pk_i, from, roots)?;
+    if own_row.get(&from).map(|own| own.value) != Some(share.value) {
+        return Err(Error::InconsistentValue { from: from });
+    }
+    Ok(())
+}
+
+//A reconstructor's publication: per forwarding receiver its index, the length of its row and the row as in encode_row
+pub(crate) fn encode_publication<F: Field>(publication: &BTreeMap<u64, BTreeMap<u64, Subshare<F>>>) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for (from, row) in publication {
+        let row = encode_row(row)?;
+        bytes.extend_from_slice(&from.to_le_bytes());
+        bytes.extend_from_slice(&(row.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&row);
+    }
+    Ok(bytes)
+}
+
+fn decode_publication<F: Field>(mut bytes: &[u8]) -> Option<BTreeMap<u64, BTreeMap<u64, Subshare<F>>>> {
+    let mut publication = BTreeMap::new();
+    while !bytes.is_empty() {
+        let from = u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?);
+        let len = usize::try_from(u64::from_le_bytes(bytes.get(8..16)?.try_into().ok()?)).ok()?;
+        let row = decode_row(bytes.get(16..16usize.checked_add(len)?)?).ok()?;
+        publication.insert(from, row);
+        bytes = &bytes[16 + len..];
+    }
+    Some(publication)
+}
+
+//Total serialized size of a batch of subshares
+fn serialized_size<'a, F: Field + 'a>(shares: impl Iterator<Item = &'a Subshare<F>>) -> u64 {
+    shares.map(|share| share.serialized_size()).sum()
+}
+
 
 
 impl<F: Field> VSS<F> {
-    pub fn execute(&self) {
-        let t = self.pp.t;
-        let n = self.pp.n;
+    ///Runs the protocol and collects every role's cost into a CostReport
+    ///With `execution_leaks` every role's state goes to a LeakageAdversary after it speaks
+    pub fn execute(&self) -> Result<VssReport<F>> {
+        self.execute_against(&mut Honest)
+    }
 
-        let mut dealer_time = Duration::new(0,0);
-        let mut receiver_time: Vec<Duration> = Vec::new();
-        let mut reconstructor_time = Duration::new(0,0);
-        let mut client_time = Duration::new(0,0);
+    ///Runs the protocol with the parties `byzantine` corrupts following its behavior
+    pub fn execute_against<A: Adversary>(&self, byzantine: &mut A) -> Result<VssReport<F>> {
+        self.run(byzantine, None)
+    }
 
+    ///Like execute_against, and also returns the transcript of every message and the reconstructed secrets
+    pub fn execute_recorded<A: Adversary>(&self, byzantine: &mut A) -> Result<(VssReport<F>, Transcript)> {
+        let mut transcript = Transcript::new(Protocol::Vss { t: self.pp.t, n: self.pp.n, packing: self.pp.packing });
+        let report = self.run(byzantine, Some(&mut transcript))?;
+        report.secrets.serialize_compressed(&mut transcript.output)?;
+        Ok((report, transcript))
+    }
 
-        let mut dealer_comm =0.0;
-        let mut receiver_comm: Vec<f64> = Vec::new();
-        let mut reconstructor_comm = 0.0;
-        let mut client_comm = 0.0;
+    fn run<A: Adversary>(&self, byzantine: &mut A, mut transcript: Option<&mut Transcript>) -> Result<VssReport<F>> {
+        let mut costs = CostReport::default();
+        let mut adversary: Option<LeakageAdversary<F>> = None;
+        if self.execution_leaks {
+            adversary = Some(LeakageAdversary::new(self.pp.t, self.pp.n, self.seed));
+        }
+        let sharing = self.share_phase(&mut costs, adversary.as_mut(), byzantine, transcript.as_deref_mut())?;
+        let (reconstructed, secrets) = self.reconstruct_phase(&sharing, &mut costs, adversary.as_mut(), byzantine, transcript)?;
+        let (points, secret_points) = points_of::<F>(&sharing.pki[1..], self.pp.packing)?;
+        let leakage = adversary.map(|adversary| adversary.report(&points, &secret_points, self.pp.degree()));
+        let leakage_harmless = leakage.as_ref().is_none_or(|leakage| leakage.secrecy_holds && leakage.forgeries_accepted == 0);
+        Ok(VssReport {
+            costs: costs,
+            success: reconstructed && secrets == self.secrets && leakage_harmless,
+            secrets: secrets,
+            reconstructed: reconstructed,
+            leakage: leakage,
+            complaints: sharing.complaints,
+            accusations: sharing.accusations
+        })
+    }
 
-        //let mut pcs: Vec<Vec<PrivateChannel>> = vec![Vec::new(); n_parties_total as usize];
-        let mut pki: Vec<PublicKey> = Vec::new();
-        let mut dealer: Dealer<F> = Dealer{secret: self.secret, pp: &self.pp, keypair: None};
+    ///Dealer and receivers: everything up to the triply signed subshares reaching the reconstructors.
+    ///Bad messages end up as complaints and accusations, an error means the honest parties themselves could not proceed.
+    ///Messages that go out are recorded in `transcript` if one is given.
+    pub fn share_phase<M: Metrics, A: Adversary>(&self, metrics: &mut M, mut adversary: Option<&mut LeakageAdversary<F>>, byzantine: &mut A, mut transcript: Option<&mut Transcript>) -> Result<Sharing<F>> {
+        let n = self.pp.n;
+        let mut complaints = 0;
+        let mut accusations: BTreeMap<u64, BTreeSet<u64>> = BTreeMap::new();
+
+        //Role keys were published by the nominating committee before the dealing starts and fix the evaluation points
+        let pki: Vec<PublicKey> = self.pp.roles.signing_keys();
+        let encryption_keys: Vec<EncryptionKey> = self.pp.roles.encryption_keys();
+        if pki.len() as u64 != n + 1 {
+            return Err(Error::InvalidParams(format!("{} roles were nominated for a dealer and {} receivers", pki.len(), n)));
+        }
+        let (points, _) = points_of::<F>(&pki[1..], self.pp.packing)?;
+        if let Some(transcript) = transcript.as_deref_mut() {
+            transcript.set_keys(&pki);
+        }
 
-        let dealer_start_time = SystemTime::now();        
-        //Dealer shares the secret, gather secret shares
-        let (shares, pk) = dealer.share();
-        let dealer_end_time = SystemTime::now();
-        dealer_time = dealer_end_time.duration_since(dealer_start_time).unwrap();
-        println!("Dealer's work takes {} milliseconds", dealer_time.as_millis());
+        let dealer: Dealer<F> = Dealer::new(self.secrets.clone(), &self.pp, self.seed)?;
+        let mut receivers: Vec<Receiver<F>> = (1..=n).map(|i| Receiver::new(i, &self.pp)).collect::<Result<Vec<Receiver<F>>>>()?;
 
-        dealer_comm = (((size_of_val(&shares[&1][&1]) as u64)*(shares.len() as u64) *(shares.len() as u64) + (size_of_val(&pk) as u64)) as f64)/1000000.0;
+        //Dealer shares the secret, gather secret shares
+        let mut shares = dealer.share(&points, metrics)?;
+        if byzantine.is_corrupted(0) {
+            byzantine.tamper_rows(0, &mut shares, &points, self.pp.degree(), dealer.keypair());
+        }
+        let channels = dealer.send_rows(&shares, &encryption_keys[1..], metrics)?;
+        if let Some(transcript) = transcript.as_deref_mut() {
+            for i in channels.keys() {
+                transcript.record(MessageKind::DealerRow, 0, Some(*i), encode_row(&shares[i])?);
+            }
+        }
+        if let Some(adversary) = adversary.as_deref_mut() {
+            adversary.leak(0, dealer.keypair(), None);
+        }
 
-        pki.push(pk);
-        //Stoore shares th
         let mut shares_signed_by_p_i: BTreeMap<u64, BTreeMap<u64, Subshare<F>>> = Default::default();
         let mut shares_triple_signed: BTreeMap<u64, BTreeMap<u64, Subshare<F>>> = Default::default();
+        let mut rows: BTreeMap<u64, Vec<F>> = BTreeMap::new();
 
         //Each receiver verifies what it got from the dealer and what it got from other parties, compute what it wants to send to other parties
         for i in 1..=n {
-            //need to forward these triply shares to the reconstructors
-            let mut expanded_shares: BTreeMap<u64, Subshare<F>> = Default::default();
-            let mut receiver_i: Receiver<F> = Receiver{ id: i, pp: &self.pp, keypair: None, share: BTreeMap::new() };
-            //need to forward these doubly shares to future receivers
+            let receiver_i: &mut Receiver<F> = &mut receivers[(i - 1) as usize];
 
-            let receiver_start_time = SystemTime::now();
-            let (receiver_i_shares_to_send, pk_p_i) =
-                                        receiver_i.receive_from_dealer(&pk, &shares[&i]);
-            pki.push(pk_p_i);
+            //need to forward these doubly shares to future receivers
+            let mut receiver_i_shares_to_send = receiver_i.receive_from_dealer(&pki[0], channels.get(&i), &points, metrics)?;
+            if byzantine.is_corrupted(i) {
+                byzantine.tamper_doubly_signed(i, &mut receiver_i_shares_to_send, receiver_i.keypair(), &pki[0]);
+            }
+            if let Some(transcript) = transcript.as_deref_mut() {
+                for (k, share) in receiver_i_shares_to_send.iter().filter(|(k, _)| **k != i) {
+                    transcript.record(MessageKind::DoublySignedSubshare, i, Some(*k), encode_row(&BTreeMap::from([(*k, share.clone())]))?);
+                }
+            }
             shares_signed_by_p_i.insert(i, receiver_i_shares_to_send);
+
+            //need to forward these triply shares to the reconstructors
+            let mut expanded_shares: BTreeMap<u64, Subshare<F>> = Default::default();
             for j in 1..=i {
-                let (happy, expanded_share) = receiver_i.receive_from_party(j, 
-                                                        &shares_signed_by_p_i[&j][&i],
+                let share = match shares_signed_by_p_i[&j].get(&i) {
+                    Some(share) => share.clone(),
+                    None => {
+                        receiver_i.missing_from(j);
+                        continue;
+                    }
+                };
+
+                //Equivocation: p_j has already spoken, so the adversary holds its key and the dealer's and gets a different subshare in first
+                if let Some(adversary) = adversary.as_deref_mut().filter(|_| j < i) {
+                    if let Some(forged) = adversary.forge(share.value + F::ONE, (j, i), &[j]) {
+                        //Checking forgeries is not charged to the protocol
+                        if let Ok(expanded_share) = receiver_i.receive_from_party(j, &forged, &pki[0], &pki[j as usize], &mut CostReport::default()) {
+                            expanded_shares.insert(j, expanded_share);
+                            adversary.forgery_accepted();
+                        }
+                    }
+                }
+
+                if let Ok(expanded_share) = receiver_i.receive_from_party(j,
+                                                        &share,
                                                         &pki[0],
-                                                        &pki[j as usize]);
-                if happy {
-                    expanded_shares.insert(j, expanded_share);
+                                                        &pki[j as usize],
+                                                        metrics) {
+                    expanded_shares.entry(j).or_insert(expanded_share);
                 }
             }
+            let mut expanded_shares = receiver_i.countersign(expanded_shares, &pki[0], metrics)?;
+            if byzantine.is_corrupted(i) {
+                byzantine.tamper_forwarded(i, &mut expanded_shares, receiver_i.keypair(), &pki[0]);
+            }
+            let forwarded = receiver_i.forward_to_reconstructors(expanded_shares, metrics);
+            if let Some(transcript) = transcript.as_deref_mut() {
+                let bytes = encode_row(&forwarded)?;
+                for j in 1..=self.pp.t+1 {
+                    transcript.record(MessageKind::TriplySignedSubshares, i, Some(self.pp.reconstructor_id(j)), bytes.clone());
+                }
+            }
+            shares_triple_signed.insert(i, forwarded);
 
-            let receiver_end_time = SystemTime::now();
-            receiver_time.push(receiver_end_time.duration_since(receiver_start_time).unwrap());
-
-            receiver_comm.push((((size_of_val(&expanded_shares[&1]) as u64)*(expanded_shares.len() as u64)*(t+1) +
-            (size_of_val(&shares_signed_by_p_i[&1][&1]) as u64)*(shares_signed_by_p_i[&1].len() as u64) + 
-            (size_of_val(&pk_p_i) as u64)) as f64)/1000000.0);
-            
-            shares_triple_signed.insert(i, expanded_shares);
-        }
-
-        //Reconstructors publish projections that they received
-        for _i in 1..=t+1 {
-            let reconstructor: Reconstructor<F> = Reconstructor{pp: &self.pp, _marker: std::marker::PhantomData};
-            for _j in 1..=n {
-                reconstructor.receive_from_party(_j, &shares_triple_signed[&_j], &pki);
+            if let Some(adversary) = adversary.as_deref_mut() {
+                adversary.leak(i, receiver_i.keypair(), Some(&receiver_i.share));
+            }
+            if receiver_i.complains() {
+                complaints += 1;
+            } else {
+                rows.insert(i, receiver_i.share.values().map(|share| share.value).collect());
+            }
+            for accused in &receiver_i.accused {
+                accusations.entry(*accused).or_default().insert(i);
             }
         }
-        reconstructor_comm = (((size_of_val(&shares_triple_signed[&1][&1]) as f64)*dbg!(shares_triple_signed.len()) as f64 )*dbg!(shares_triple_signed[&1].len())
-    as f64)/1000000.0;
 
-        let client: Client<F> = Client { pp: &self.pp, _marker: std::marker::PhantomData };
+        Ok(Sharing { pki: pki, shares_triple_signed: shares_triple_signed, rows: rows, complaints: complaints, accusations: accusations })
+    }
 
-        let client_start_time = SystemTime::now();
-        let secret: (bool, F) = client.compute_secret(&shares_triple_signed, &pki);
-        let client_end_time = SystemTime::now();
-        client_time = client_end_time.duration_since(client_start_time).unwrap();
-        println!("Dealer's work takes {} milliseconds", dealer_time.as_millis());
-        println!("First receiver's work takes {} milliseconds", receiver_time[0].as_millis());
-        println!("Last receiver's work takes {} milliseconds", receiver_time[(n - 1) as usize].as_millis());
-        println!("Client's work takes {} milliseconds", client_time.as_millis());
+    ///Reconstructors and client: publishes the forwarded subshares and recovers the secrets from all t+1 publications
+    pub fn reconstruct_phase<M: Metrics, A: Adversary>(&self, sharing: &Sharing<F>, metrics: &mut M, mut adversary: Option<&mut LeakageAdversary<F>>, byzantine: &mut A, mut transcript: Option<&mut Transcript>) -> Result<(bool, Vec<F>)> {
+        let t = self.pp.t;
+        let n = self.pp.n;
+        let pki = &sharing.pki;
+        let shares_triple_signed = &sharing.shares_triple_signed;
 
+        //Reconstructors publish projections that they received
+        let mut publications: Vec<BTreeMap<u64, BTreeMap<u64, Subshare<F>>>> = Vec::new();
+        for j in 1..=t+1 {
+            let mut reconstructor: Reconstructor<F> = Reconstructor{ id: j, pp: &self.pp, received: BTreeMap::new() };
+            let mut forgeries: Vec<(u64, u64, F)> = Vec::new();
+            for from in 1..=n {
+                //A receiver that forwarded nothing is published as an empty row
+                let forwarded = shares_triple_signed.get(&from).cloned().unwrap_or_default();
+
+                //Once p_from has spoken the adversary re-signs one of its subshares with a different value and gets the message in first
+                if let Some(adversary) = adversary.as_deref_mut() {
+                    let mut forged_shares = forwarded.clone();
+                    if let Some((&k, share)) = forwarded.iter().next() {
+                        if let Some(forged) = adversary.forge(share.value + F::ONE, (k, from), &[k, from]) {
+                            forgeries.push((from, k, forged.value));
+                            forged_shares.insert(k, forged);
+                            let _ = reconstructor.receive_from_party(from, &forged_shares, pki, &mut CostReport::default());
+                        }
+                    }
+                }
 
-        println!("Dealer requires {} MB", dealer_comm);
-        println!("First receiver requires {} MB", receiver_comm[0]);
-        println!("Last receiver requires {} MB", receiver_comm[receiver_comm.len() - 1]);
-        println!("Reconstructor requires {} MB", reconstructor_comm);
+                if let Err(error) = reconstructor.receive_from_party(from, &forwarded, pki, metrics) {
+                    metrics.record_rejection(self.pp.reconstructor_id(j), Role::Reconstructor, &error);
+                }
+            }
+            if let Some(adversary) = adversary.as_deref_mut() {
+                for (from, k, value) in forgeries {
+                    if reconstructor.received.get(&from).and_then(|row| row.get(&k)).is_some_and(|share| share.value == value) {
+                        adversary.forgery_accepted();
+                    }
+                }
+            }
+            let mut publication = reconstructor.publish(metrics);
+            if byzantine.is_corrupted(self.pp.reconstructor_id(j)) {
+                byzantine.tamper_publication(self.pp.reconstructor_id(j), &mut publication);
+            }
+            if let Some(transcript) = transcript.as_deref_mut() {
+                transcript.record(MessageKind::Publication, self.pp.reconstructor_id(j), None, encode_publication(&publication)?);
+            }
+            publications.push(publication);
+        }
 
+        //At most t reconstructors are corrupted, so every row an honest one got is in some publication
+        let client: Client<F> = Client::new(t, n).with_packing(self.pp.packing);
+        match client.compute_secret(&publications, pki, metrics) {
+            Ok(secrets) => Ok((true, secrets)),
+            Err(error) => {
+                metrics.record_rejection(self.pp.client_id(), Role::Client, &error);
+                Ok((false, vec![F::ZERO; self.pp.packing as usize]))
+            }
+        }
+    }
+}
 
-        let mut time_per_party: Vec<Duration> = Vec::new();
-        let mut comm_per_party: Vec<f64> = Vec::new();
+impl<F: Field> RandExtractorVSSBased<F> {
+    pub fn new(pp: PubParams, execution_leaks: bool, seed: Option<u64>) -> RandExtractorVSSBased<F> {
+        RandExtractorVSSBased { pp: pp, execution_leaks: execution_leaks, seed: seed, _marker: std::marker::PhantomData }
+    }
 
-        let mut overall_time = 0;
-        let mut overall_comm = 0.0;
+    pub fn execute(&self) -> Result<BeaconOutcome<F>> {
+        self.execute_against(&mut Honest)
+    }
 
-        for i in 1..=5*t + 4 {
-            time_per_party.push(Duration::new(0,0));
-            comm_per_party.push(0.0);
-        }
+    ///Runs the beacon with the parties `byzantine` corrupts following its behavior, in every dealing they take part in
+    pub fn execute_against<A: Adversary>(&self, byzantine: &mut A) -> Result<BeaconOutcome<F>> {
+        self.run(byzantine, None)
+    }
 
-        //first t+1 parties are acting as dealers
-        for i in 1..=t + 1 {
-            time_per_party[(i - 1) as usize] += dealer_time;
-            comm_per_party[(i - 1) as usize] += dealer_comm;
+    ///Like execute_against, and also returns the transcript of every dealing, each in the session of its dealer, and the output
+    pub fn execute_recorded<A: Adversary>(&self, byzantine: &mut A) -> Result<(BeaconOutcome<F>, Transcript)> {
+        let mut transcript = Transcript::new(Protocol::Beacon { t: self.pp.t, n: self.pp.n, packing: self.pp.packing });
+        let outcome = self.run(byzantine, Some(&mut transcript))?;
+        outcome.output.serialize_compressed(&mut transcript.output)?;
+        Ok((outcome, transcript))
+    }
 
-            for k in (i + 1)..=3*t + i + 1 {
-                time_per_party[(k - 1) as usize] += receiver_time[(k - 1 - i) as usize];
-                comm_per_party[(k - 1) as usize] += receiver_comm[(k - 1 - i) as usize];
+    fn run<A: Adversary>(&self, byzantine: &mut A, mut transcript: Option<&mut Transcript>) -> Result<BeaconOutcome<F>> {
+        let t = self.pp.t;
+        let n = self.pp.n;
+        let start_time = SystemTime::now();
+        let mut rng: StdRng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::seed_from_u64(rand::random()),
+        };
+
+        //Sharing phase: every member of the committee deals random secrets, one per packed slot, to the whole committee.
+        //Dealer i deals with the keys of its receiver role, so it is receiver i in every dealing, its own included.
+        let mut dealings: Vec<(u64, VSS<F>, Sharing<F>, CostReport, Option<LeakageAdversary<F>>)> = Vec::new();
+        for dealer in 1..=n {
+            let vss: VSS<F> = VSS {
+                secrets: (0..self.pp.packing).map(|_| F::rand(&mut rng)).collect(),
+                pp: self.pp.dealing_to(dealer, &self.pp)?,
+                execution_leaks: self.execution_leaks,
+                seed: rng.gen()
+            };
+            let mut costs = CostReport::default();
+            let mut adversary: Option<LeakageAdversary<F>> = None;
+            if self.execution_leaks {
+                adversary = Some(LeakageAdversary::new(t, n, vss.seed));
             }
+            if let Some(transcript) = transcript.as_deref_mut() {
+                transcript.start_session(dealer);
+            }
+            let sharing = vss.share_phase(&mut costs, adversary.as_mut(), &mut DealingView { adversary: byzantine, dealer: dealer }, transcript.as_deref_mut())?;
+            dealings.push((dealer, vss, sharing, costs, adversary));
         }
 
-        for k in 4*t + 4..=5*t + 4 {
-            comm_per_party[(k - 1) as usize] += reconstructor_comm;
-        }
-
-        for i in 1..=5*t + 4 {
-            overall_time += time_per_party[(i-1) as usize].as_millis();
-            overall_comm += comm_per_party[(i-1) as usize];
+        //The qualified set is fixed before anything is reconstructed, so no dealer can adapt its secret to the others
+        let complaints: BTreeMap<u64, u64> = dealings.iter().map(|(dealer, _, sharing, _, _)| (*dealer, sharing.complaints)).collect();
+        let qualified = Resolver { t: t }.qualify(&complaints);
+
+        //Reconstruction phase: open the qualified dealings and add up their secrets slot by slot
+        let mut output = vec![F::ZERO; self.pp.packing as usize];
+        let mut success = true;
+        let mut costs_per_dealing: BTreeMap<u64, CostReport> = BTreeMap::new();
+        for (dealer, vss, sharing, mut costs, mut adversary) in dealings {
+            if qualified.contains(&dealer) {
+                if let Some(transcript) = transcript.as_deref_mut() {
+                    transcript.start_session(dealer);
+                }
+                let (reconstructed, secrets) = vss.reconstruct_phase(&sharing, &mut costs, adversary.as_mut(), &mut DealingView { adversary: byzantine, dealer: dealer }, transcript.as_deref_mut())?;
+                add_values(&mut output, &secrets);
+                //A corrupted dealer's secrets are whatever its consistent rows determine
+                success &= reconstructed && (secrets == vss.secrets || byzantine.is_corrupted(dealer));
+            }
+            if let Some(adversary) = adversary {
+                let (points, secret_points) = points_of::<F>(&sharing.pki[1..], self.pp.packing)?;
+                let leakage = adversary.report(&points, &secret_points, self.pp.degree());
+                success &= leakage.secrecy_holds && leakage.forgeries_accepted == 0;
+            }
+            costs_per_dealing.insert(dealer, costs);
         }
 
-        overall_time += client_time.as_millis();
-
-        println!("Overall time: {}", overall_time);
-        println!("Overall comm: {}", overall_comm);
+        Ok(BeaconOutcome {
+            output: output,
+            qualified: qualified,
+            dealings: costs_per_dealing,
+            complaints: complaints,
+            success: success,
+            time: SystemTime::now().duration_since(start_time).unwrap()
+        })
+    }
+}
 
+impl<F: Field> BeaconOutcome<F> {
+    pub fn total(&self) -> RoleCost {
+        let mut total = RoleCost::default();
+        for costs in self.dealings.values() {
+            let dealing = costs.total();
+            total.messages += dealing.messages;
+            total.bytes += dealing.bytes;
+            total.time += dealing.time;
+        }
+        total
+    }
 
-        dbg!(secret);
+    pub fn print(&self) {
+        println!("dealer | complaints | qualified | time ms | comm MB");
+        for (dealer, costs) in &self.dealings {
+            let total = costs.total();
+            println!("{} | {} | {} | {} | {}", dealer, self.complaints.get(dealer).copied().unwrap_or_default(), self.qualified.contains(dealer), total.time.as_millis(), (total.bytes as f64)/1000000.0);
+        }
+        let total = self.total();
+        println!("Whole protocol takes {} milliseconds", self.time.as_millis());
+        println!("Overall time: {}", total.time.as_millis());
+        println!("Overall comm: {}", (total.bytes as f64)/1000000.0);
+        println!("Output: {}, success: {}", display_values(&self.output), self.success);
+    }
+}
 
+impl<F: Field> VssReport<F> {
+    pub fn print(&self) {
+        self.costs.print_per_party();
+        self.costs.print_by_kind();
+        if let Some(leakage) = &self.leakage {
+            leakage.print();
+        }
+        println!("Secret reconstructed: {}, secret: {}, success: {}", self.reconstructed, display_values(&self.secrets), self.success);
     }
 }
 
-impl<F: Field> Dealer<'_, F> {
-    fn set_key_pair(&mut self) {
-        // Generating signature key pair
-        //let (pk, sk) = keypair();
-        //self.sk = Some(sk);
-        //self.pk = Some(pk);
+impl<'a, F: Field> Dealer<'a, F> {
+    ///The dealer of `pp`'s committee, with the keys nominated for its role; it shares one secret per packed slot
+    pub fn new(secrets: Vec<F>, pp: &'a PubParams, seed: u64) -> Result<Dealer<'a, F>> {
+        if secrets.len() as u64 != pp.packing {
+            return Err(Error::InvalidParams(format!("{} secrets for {} packed slots", secrets.len(), pp.packing)));
+        }
+        let keys = pp.claim_role(pp.dealer_id())?;
+        Ok(Dealer { pp: pp, secrets: secrets, seed: seed, keypair: keys.keypair })
+    }
 
-        //let sig_pp = SchnorrEdwards::setup(&mut test_rng()).unwrap();
-        //let (pk,sk) = SchnorrEdwards::keygen(&sig_pp, &mut test_rng()).unwrap();
-        let mut csprng = OsRng{};
-        let keypair: Keypair = Keypair::generate(&mut csprng);
-        self.keypair = Some(keypair);
+    pub(crate) fn keypair(&self) -> &Keypair {
+        &self.keypair
     }
 
-    pub fn share(&mut self) -> (BTreeMap<u64, BTreeMap<u64, Subshare<F>>>, PublicKey) {
+    ///The random symmetric bivariate polynomial holding the secrets on its diagonal, drawn from the dealer's seed
+    pub fn polynomial(&self) -> Result<SymBVPoly<F>> {
+        let mut csprng: StdRng = StdRng::seed_from_u64(self.seed);
+        Ok(SymBVPoly::rand_with_diagonal(self.pp.degree(), &secret_points(self.pp.packing), &self.secrets, &mut csprng)?)
+    }
+
+    ///Endorsed rows for the receivers, receiver i being evaluated at points[i-1]
+    pub fn share<M: Metrics>(&self, points: &[F], metrics: &mut M) -> Result<BTreeMap<u64, BTreeMap<u64, Subshare<F>>>> {
         let n = self.pp.n;
+        let start_time = SystemTime::now();
 
-        let mut csprng: StdRng = StdRng::seed_from_u64(6);
-        // Generating random symmetric bivariate polynomial to hold the secret
-        let poly = SymBVPoly::rand(self.pp.t, &mut csprng);
+        let poly = self.polynomial()?;
 
-        // Generate and set a signature key pair
-        self.set_key_pair();
+        let keypair = &self.keypair;
 
-        let keypair = self.keypair.as_ref().unwrap();
+        // Evaluate the rows of all parties, one row per thread if parallel is enabled
+        let values: Vec<Vec<F>> = cfg_into_iter!(1..=n)
+            .map(|i| (1..=n).map(|j| poly.eval(points[(i - 1) as usize], points[(j - 1) as usize])).collect())
+            .collect();
 
-        // Generate signed shares for all parties
-        let shares: BTreeMap<u64, BTreeMap<u64, Subshare<F>>> = (1..=n)
-            .map(|i| {
-                (
-                    i,
-                    (1..=n)
-                        .map(|j| {
-                            //Compute the share
-                            let share = poly.eval(F::from(i), F::from(j));
+        //Endorse every entry, or commit to the whole matrix with one signed Merkle root
+        let leaves = (1..=n).zip(&values)
+            .flat_map(|(i, row)| (1..=n).zip(row).map(move |(j, value)| signed_bytes(value, &keypair.public, i, j)))
+            .collect::<Result<Vec<Vec<u8>>>>()?;
+        let mut endorsements = endorse(&leaves, keypair, self.pp.authentication)?.into_iter();
+        let shares: BTreeMap<u64, BTreeMap<u64, Subshare<F>>> = (1..=n).zip(values)
+            .map(|(i, row)| {
+                let row = (1..=n).zip(row)
+                    .zip(endorsements.by_ref())
+                    .map(|((j, share), endorsement)| (j, Subshare { value: share, dealer_signature: Some(endorsement), p_i_signature: None, p_j_signature: None }))
+                    .collect();
+                (i, row)
+            })
+            .collect();
 
-                            //Serialize and generate dealer's signature
-                            let mut share_bytes = Vec::new();
-                            //Try (share, F::from(i), F::from(j)) here instead
-                            share.serialize_uncompressed(&mut share_bytes).unwrap();
+        //The rows are sent in send_rows
+        let time = SystemTime::now().duration_since(start_time).unwrap();
+        metrics.record(self.pp.dealer_id(), Role::Dealer, 0, 0, time);
 
-                            let dealer_signature = keypair.sign(&mut share_bytes); 
+        Ok(shares)
+    }
 
-                            (j, Subshare { value: share, dealer_signature: Some(dealer_signature), p_i_signature: None, p_j_signature: None })
-                        })
-                        .collect(),
-                )
-            })
-            .collect();
+    ///Sends row i over a private channel encrypted to encryption_keys[i-1]
+    pub fn send_rows<M: Metrics>(&self, shares: &BTreeMap<u64, BTreeMap<u64, Subshare<F>>>, encryption_keys: &[EncryptionKey], metrics: &mut M) -> Result<BTreeMap<u64, PrivateChannel>> {
+        let start_time = SystemTime::now();
+        let mut channels: BTreeMap<u64, PrivateChannel> = BTreeMap::new();
+        for (i, row) in shares {
+            //Rows for receivers that do not exist have nobody to go to
+            let encryption_key = match i.checked_sub(1).and_then(|index| encryption_keys.get(index as usize)) {
+                Some(encryption_key) => encryption_key,
+                None => continue,
+            };
+            let mut channel = PrivateChannel::new(*encryption_key);
+            channel.store_msg(&encode_row(row)?)?;
+            channels.insert(*i, channel);
+        }
 
-        (shares,  keypair.public)
+        let time = SystemTime::now().duration_since(start_time).unwrap();
+        metrics.record(self.pp.dealer_id(), Role::Dealer, 0, 0, time);
+        metrics.record_messages(self.pp.dealer_id(), Role::Dealer, MessageKind::DealerRow, channels.len() as u64, channels.values().map(|channel| channel.serialized_size()).sum());
+        Ok(channels)
     }
 }
 
 
-impl<F: Field> Receiver<'_, F> {
-    fn set_key_pair(&mut self) {
-        // Generating signature key pair
-        let mut csprng = OsRng{};
-        let keypair: Keypair = Keypair::generate(&mut csprng);
-        self.keypair = Some(keypair);
+impl<'a, F: Field> Receiver<'a, F> {
+    ///Receiver `id` of `pp`'s committee, with the keys nominated for its role
+    pub fn new(id: u64, pp: &'a PubParams) -> Result<Receiver<'a, F>> {
+        let keys = pp.claim_role(id)?;
+        Ok(Receiver { id: id, pp: pp, keypair: keys.keypair, role_key: keys.role_key, share: BTreeMap::new(), complaints: 0, accused: BTreeSet::new(), heard_from: BTreeSet::new(), disputed: BTreeSet::new(), agreed: BTreeSet::new(), roots: VerifiedRoots::default() })
     }
-    
-    pub fn receive_from_dealer(&mut self, dealer_pk: &PublicKey, share: &BTreeMap<u64, Subshare<F>>) 
-                                                    -> (BTreeMap<u64, Subshare<F>>, PublicKey) {
-        let n = self.pp.n;
-        let t = self.pp.t;      
-        self.set_key_pair();
-        let keypair = self.keypair.as_ref().unwrap();
 
-        let x_vals: Vec<u64> = share.keys().cloned().collect();
-        let yvals_signed: Vec<Subshare<F>> = share.values().cloned().collect();
+    ///Whether we complain about the dealer: its row is bad, or a receiver's subshare disagrees with it
+    ///and none of that receiver's subshares agrees
+    pub fn complains(&self) -> bool {
+        self.complaints > 0 || !self.disputed.is_subset(&self.agreed)
+    }
 
-        //Verify whether each subshare is correctly signed by the dealer
-        for subshare in yvals_signed {
-            let message = subshare.value;
-            //let dealer_msg = open(&subshare.dealer_signature.unwrap(), &dealer_pk).unwrap();
-            //let uncompressed_dealer_msg: F = F::deserialize_uncompressed(&*dealer_msg).unwrap();
-            let mut share_bytes = Vec::new();
-            message.serialize_uncompressed(&mut share_bytes).unwrap();
-            if !dealer_pk.verify(&share_bytes, &subshare.dealer_signature.unwrap()).is_ok() {
-                println!("I'm unhappy!")
-            } 
+    ///Records that p_from never sent its subshare
+    pub fn missing_from(&mut self, from: u64) {
+        if self.heard_from.insert(from) {
+            self.accused.insert(from);
         }
+    }
 
-        //let yvals_signed: Vec<Subshare<F>> = share.values().cloned().collect();
-        let y_vals: Vec<F> = share.values().into_iter().map(|signed_share| signed_share.value).collect();
+    pub(crate) fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
 
-        let unipoly: Poly<F> = Poly::evals_to_coeffs(&x_vals, &y_vals, n);
+    //Decrypts and parses the row; a missing row is empty
+    fn open_row(&self, channel: Option<&PrivateChannel>) -> Result<BTreeMap<u64, Subshare<F>>> {
+        let row = channel.map_or(Ok(None), |channel| channel.read_msg_from_round(&self.role_key))?;
+        Ok(row.map(|bytes| decode_row(&bytes)).transpose()?.unwrap_or_default())
+    }
 
-        if unipoly.degree > t {
-            println!("I'm unhappy!")
+    ///Opens the dealer's row with our role key and checks it; entry k of the row is the evaluation at points[k-1]
+    ///and the row must have the degree of the sharing.
+    ///A bad row only leads to complaints; the error is for failing to sign our own subshares.
+    pub fn receive_from_dealer<M: Metrics>(&mut self, dealer_pk: &PublicKey, channel: Option<&PrivateChannel>, points: &[F], metrics: &mut M)
+                                                    -> Result<BTreeMap<u64, Subshare<F>>> {
+        let n = self.pp.n;
+        let start_time = SystemTime::now();
+        //A row that does not decrypt or does not parse is empty
+        let share = &self.open_row(channel).unwrap_or_else(|error| {
+            metrics.record_rejection(self.id, Role::Receiver, &error);
+            BTreeMap::new()
+        });
+
+        for error in row_problems(self.id, share, dealer_pk, points, n, self.pp.degree()) {
+            metrics.record_rejection(self.id, Role::Receiver, &error);
+            self.complaints += 1;
         }
 
         //I'm happy, preparing doubly signed subshares 
-        let subshares_doubly_signed: BTreeMap<u64, Subshare<F>> = (self.id..=n)
-                                    .map(|k| {
-                                        let mut share_bytes = Vec::new();
-                                        share[&k].value.serialize_uncompressed(&mut share_bytes).unwrap();
-                                        let p_i_signature = keypair.sign(&mut share_bytes); 
-                                        let dealer_signature = share[&k].dealer_signature.clone();
-                                        (k, Subshare{ value: share[&k].value, dealer_signature: dealer_signature, p_i_signature: Some(p_i_signature), p_j_signature: None })
-                                    } )
+        let leaves = share.range(self.id..=n).map(|(k, subshare)| signed_bytes(&subshare.value, dealer_pk, self.id, *k)).collect::<Result<Vec<Vec<u8>>>>()?;
+        let subshares_doubly_signed: BTreeMap<u64, Subshare<F>> = share.range(self.id..=n)
+                                    .zip(endorse(&leaves, &self.keypair, self.pp.authentication)?)
+                                    .map(|((k, subshare), p_i_signature)| (*k, Subshare{ value: subshare.value, dealer_signature: subshare.dealer_signature.clone(), p_i_signature: Some(p_i_signature), p_j_signature: None }))
                                     .collect();
         self.share = share.clone();
-        (subshares_doubly_signed, keypair.public)
-    }
-
-    pub fn receive_from_party(&self, from: u64, share: &Subshare<F>, dealer_pk: &PublicKey, pk_i: &PublicKey) -> (bool,Subshare<F>) {
-        let mut happy = true; //I'm happy
-        let keypair = self.keypair.as_ref().unwrap();
-        //verify dealer's signature first
-        let message = share.value;
-        let mut share_bytes = Vec::new();
-        message.serialize_uncompressed(&mut share_bytes).unwrap();
-        //let dealer_msg = open(&share.dealer_signature.clone().unwrap(), dealer_pk).unwrap();
-        //let dealer_msg_uncompressed: F = F::deserialize_uncompressed(&*dealer_msg).unwrap();
-        
-        //let p_i_msg = open(&share.p_i_signature.clone().unwrap(), pki).unwrap();
-        //let p_i_uncompressed: F = F::deserialize_uncompressed(&*p_i_msg).unwrap();
-
-        if !dealer_pk.verify(&share_bytes, &share.dealer_signature.unwrap()).is_ok() ||
-            !pk_i.verify(&share_bytes, &share.p_i_signature.unwrap()).is_ok() {
-            happy = false; //Unhappy because one of the signatures does not verify :(
-            println!("I'm unhappy!")
-        } else { 
-            if self.share[&from].value != share.value {
-                happy = false; //Unhappy because party claims different message :(
-                println!("Must complain!"); //dealer malicious
-            } 
-            //dbg!(self.i, from);
+
+        //One subshare to each later receiver, the own one stays local
+        let time = SystemTime::now().duration_since(start_time).unwrap();
+        let sent: Vec<&Subshare<F>> = subshares_doubly_signed.iter().filter(|(k, _)| **k != self.id).map(|(_, share)| share).collect();
+        metrics.record(self.id, Role::Receiver, 0, 0, time);
+        metrics.record_messages(self.id, Role::Receiver, MessageKind::DoublySignedSubshare, sent.len() as u64, serialized_size(sent.into_iter()));
+
+        Ok(subshares_doubly_signed)
+    }
+
+    ///Checks a subshare p_from sent and returns it to be countersigned; only the first subshare from each party can lead to an accusation.
+    ///If both endorsements verify but the value differs from ours only the dealer can be at fault, otherwise p_from is.
+    ///Once p_from has spoken its key may have leaked, so a disagreeing subshare next to an agreeing one does not count against the dealer.
+    pub fn receive_from_party<M: Metrics>(&mut self, from: u64, share: &Subshare<F>, dealer_pk: &PublicKey, pk_i: &PublicKey, metrics: &mut M) -> Result<Subshare<F>> {
+        let start_time = SystemTime::now();
+        let first = self.heard_from.insert(from);
+
+        let expanded_share = check_subshare(&self.share, from, self.id, share, dealer_pk, pk_i, &self.roots).map(|_| share.clone());
+        match &expanded_share {
+            Err(error @ Error::InconsistentValue { .. }) => {
+                //The dealer is malicious
+                metrics.record_rejection(self.id, Role::Receiver, error);
+                self.disputed.insert(from);
+            },
+            Err(error) => {
+                metrics.record_rejection(self.id, Role::Receiver, error);
+                if first {
+                    self.accused.insert(from);
+                }
+            },
+            Ok(_) => {
+                self.agreed.insert(from);
+            }
         }
-        
-        let dealer_signature = share.dealer_signature.clone();
-        let p_i_signature = share.p_i_signature.clone();        
-        //let mut message_bytes = Vec::new();
-        //share.value.serialize_uncompressed(&mut message_bytes).unwrap();
-        let p_j_signature = keypair.sign(&mut share_bytes);
-        let expanded_share: Subshare<F> = Subshare {value: share.value, dealer_signature: dealer_signature, p_i_signature: p_i_signature, p_j_signature: Some(p_j_signature)};
 
-        return (happy, expanded_share)
+        metrics.record(self.id, Role::Receiver, 0, 0, SystemTime::now().duration_since(start_time).unwrap());
+        expanded_share
+    }
+
+    ///Adds our endorsement to every subshare that passed receive_from_party
+    pub fn countersign<M: Metrics>(&self, mut expanded_shares: BTreeMap<u64, Subshare<F>>, dealer_pk: &PublicKey, metrics: &mut M) -> Result<BTreeMap<u64, Subshare<F>>> {
+        let start_time = SystemTime::now();
+        let leaves = expanded_shares.iter().map(|(j, share)| signed_bytes(&share.value, dealer_pk, *j, self.id)).collect::<Result<Vec<Vec<u8>>>>()?;
+        for (share, p_j_signature) in expanded_shares.values_mut().zip(endorse(&leaves, &self.keypair, self.pp.authentication)?) {
+            share.p_j_signature = Some(p_j_signature);
+        }
+        metrics.record(self.id, Role::Receiver, 0, 0, SystemTime::now().duration_since(start_time).unwrap());
+        Ok(expanded_shares)
+    }
+
+    ///Sends the triply signed subshares to each of the t+1 reconstructors
+    pub fn forward_to_reconstructors<M: Metrics>(&self, expanded_shares: BTreeMap<u64, Subshare<F>>, metrics: &mut M) -> BTreeMap<u64, Subshare<F>> {
+        let reconstructors = self.pp.t + 1;
+        let bytes = serialized_size(expanded_shares.values())*reconstructors;
+        metrics.record_messages(self.id, Role::Receiver, MessageKind::TriplySignedSubshares, reconstructors, bytes);
+        expanded_shares
     }
 }
 
 impl<F: Field> Reconstructor<'_, F> {
-    pub fn receive_from_party(&self, from: u64, triply_signed_shares: &BTreeMap<u64, Subshare<F>>, pki: &Vec<PublicKey>) 
-                                        -> BTreeMap<u64, Subshare<F>> {
+    ///Takes the subshares p_from forwards. Only its first message is kept; a later one is rejected but can still take entries out:
+    ///a different value at the same position, endorsed by the dealer and both receivers, means their keys have leaked
+    ///and neither value can be trusted. Without the keys nobody can produce such a subshare, so nothing honest is lost.
+    pub fn receive_from_party<M: Metrics>(&mut self, from: u64, triply_signed_shares: &BTreeMap<u64, Subshare<F>>, pki: &[PublicKey], metrics: &mut M) -> Result<()> {
+        let start_time = SystemTime::now();
+        let received = match self.received.get_mut(&from) {
+            Some(kept) => {
+                let roots = VerifiedRoots::default();
+                let contradicted: Vec<u64> = triply_signed_shares.iter()
+                                                .filter(|(k, share)| kept.get(k).is_some_and(|kept_share| kept_share.value != share.value))
+                                                .filter(|(k, share)| verify_triply_endorsed(share, **k, from, pki, &roots).is_ok())
+                                                .map(|(k, _)| *k)
+                                                .collect();
+                for k in contradicted {
+                    kept.remove(&k);
+                }
+                Err(Error::DuplicateMessage { from: from })
+            },
+            None => {
+                self.received.insert(from, triply_signed_shares.clone());
+                Ok(())
+            }
+        };
+        metrics.record(self.pp.reconstructor_id(self.id), Role::Reconstructor, 0, 0, SystemTime::now().duration_since(start_time).unwrap());
+        received
+    }
+
+    ///Publishes everything received so far in a single message to the client
+    pub fn publish<M: Metrics>(&self, metrics: &mut M) -> BTreeMap<u64, BTreeMap<u64, Subshare<F>>> {
+        let bytes = serialized_size(self.received.values().flat_map(|row| row.values()));
+        metrics.record_messages(self.pp.reconstructor_id(self.id), Role::Reconstructor, MessageKind::Publication, 1, bytes);
+        self.received.clone()
+    }
+}
 
-        // let xvals: Vec<u64> = triply_signed_shares.keys().cloned().collect();
-        // //let yvals_tmp: Vec<Subshare<F>> = triply_signed_shares.values().cloned().collect();
-        // let yvals: Vec<F> = triply_signed_shares.values().map(|signed_share| signed_share.value).collect();
 
-        // let mut verified_shares: BTreeMap<u64, Subshare<F>> = Default::default();
-        // //let yvals_signed: Vec<Subshare<F>> = triply_signed_shares.values().cloned().collect();
+impl<F: Field> Client<F> {
+    pub fn new(t: u64, n: u64) -> Client<F> {
+        Client { t: t, n: n, packing: 1, roots: VerifiedRoots::default(), _marker: std::marker::PhantomData }
+    }
 
+    ///The client of a dealing with `packing` secrets
+    pub fn with_packing(mut self, packing: u64) -> Client<F> {
+        self.packing = packing;
+        self
+    }
 
-        // for key in xvals {
-        //     let share = &triply_signed_shares[&key];
-        //     //Verify share;
-        //     let message = share.value;
-        //     let dealer_msg = open(&share.dealer_signature.clone().unwrap(), &pki[0]).unwrap();
-        //     let dm_uncompressed: F = F::deserialize_uncompressed(&*dealer_msg).unwrap();
-        
-        //     let pi_msg = open(&share.p_i_signature.clone().unwrap(), &pki[key as usize]).unwrap();
-        //     let pi_uncompressed: F = F::deserialize_uncompressed(&*pi_msg).unwrap();
-
-        //     let pj_msg = open(&share.p_j_signature.clone().unwrap(), &pki[from as usize]).unwrap();
-        //     let pj_uncompressed: F = F::deserialize_uncompressed(&*pj_msg).unwrap();
-
-        //     if dm_uncompressed != message || pi_uncompressed != message || pj_uncompressed != message {
-        //         println!("I'm unhappy!");
-        //         continue;
-        //     }
-        //     let share = Subshare { value: share.clone().value, 
-        //                                         dealer_signature: share.dealer_signature.clone(), 
-        //                                         p_i_signature: share.p_i_signature.clone(),
-        //                                         p_j_signature: share.p_j_signature.clone() };
-        //     verified_shares.insert(key, share);
-        // }
+    fn degree(&self) -> u64 {
+        self.t + self.packing - 1
+    }
+
+    //Verifies the triply endorsed subshares of row i and returns the row's evaluations at the secret points, or why the row is rejected.
+    //Of the degree+t+1 subshares it takes, at least degree+1 passed an honest receiver's check against its own row.
+    pub(crate) fn verify_row(&self, i: u64, triply_signed_shares: &BTreeMap<u64, BTreeMap<u64, Subshare<F>>>, pki: &[PublicKey], points: &[F]) -> Result<Vec<F>> {
+        let n = self.n;
+        let degree = self.degree();
+        let needed = degree + self.t + 1;
+
+        let mut verified_share_keys: Vec<F> = Default::default();
+        let mut verified_share_values: Vec<F> = Default::default();
+
+        let mut shares_verified = 0;
+
+        for key in 1..=n {
+            let smaller_index;
+            let larger_index;
+            if key <= i {
+                smaller_index = key;
+                larger_index = i;
+            } else {
+                smaller_index = i;
+                larger_index = key;
+            }
+            //Subshares that were never forwarded are skipped
+            let share: &Subshare<F> = match triply_signed_shares.get(&larger_index).and_then(|row| row.get(&smaller_index)) {
+                Some(share) => share,
+                None => continue,
+            };
+
+            let message = share.value;
+            let verified = verify_triply_endorsed(share, smaller_index, larger_index, pki, &self.roots);
+            //A subshare that fails verification is skipped; too many of them and the row is rejected for lack of shares
+            if verified.is_err() {
+                continue;
+            }
+            verified_share_keys.push(points[(key - 1) as usize]);
+            verified_share_values.push(message);
+            shares_verified +=1;
+            if shares_verified >= needed {
+                break;
+            }
+        }
         
-        return triply_signed_shares.clone()
+        //Skip this party if we don't have enough verified subshares
+        if shares_verified < needed {
+            return Err(Error::NotEnoughShares { needed: needed, verified: shares_verified });
+        }
+
+        let unipoly: Poly<F> = Poly::evals_to_coeffs(&verified_share_keys, &verified_share_values)?;
+
+        if unipoly.degree > degree {
+            return Err(Error::DegreeTooHigh { degree: unipoly.degree, max: degree });
+        }
+        Ok(secret_points(self.packing).into_iter().map(|point| unipoly.eval(point)).collect())
+    }
+
+    //Row i from the first publication it verifies in; a corrupted reconstructor can leave rows out or alter them, but not make a wrong row verify
+    fn verify_row_in(&self, i: u64, publications: &[BTreeMap<u64, BTreeMap<u64, Subshare<F>>>], pki: &[PublicKey], points: &[F]) -> Result<Vec<F>> {
+        let mut rejection = Error::NotEnoughShares { needed: self.degree() + self.t + 1, verified: 0 };
+        for publication in publications {
+            match self.verify_row(i, publication, pki, points) {
+                Ok(values) => return Ok(values),
+                Err(error) => rejection = error,
+            }
+        }
+        Err(rejection)
+    }
+
+    ///Recovers the secrets, one per packed slot, from the reconstructors' publications, or says why it cannot
+    pub fn compute_secret<M: Metrics>(&self, publications: &[BTreeMap<u64, BTreeMap<u64, Subshare<F>>>], pki: &[PublicKey], metrics: &mut M) -> Result<Vec<F>> {
+        let start_time = SystemTime::now();
+        let secrets = self.interpolate_secrets(publications, pki);
+        //Same id as PubParams::client_id
+        metrics.record(self.n + self.t + 2, Role::Client, 0, 0, SystemTime::now().duration_since(start_time).unwrap());
+        secrets
+    }
+
+    fn interpolate_secrets(&self, publications: &[BTreeMap<u64, BTreeMap<u64, Subshare<F>>>], pki: &[PublicKey]) -> Result<Vec<F>> {
+        let n = self.n;
+        let rows_needed = self.degree() + 1;
+
+        //Evaluation points follow from the receivers' public keys
+        if pki.len() as u64 != n + 1 {
+            return Err(Error::NotEnoughShares { needed: n + 1, verified: pki.len() as u64 });
+        }
+        let (points, secret_points) = points_of::<F>(&pki[1..], self.packing)?;
+        let points = &points;
+
+        //Rows are verified independently; in parallel all of them are checked at once, sequentially we stop after degree+1
+        #[cfg(feature = "parallel")]
+        let verified_rows: Vec<(u64, Vec<F>)> = (1..=n).into_par_iter()
+            .filter_map(|i| self.verify_row_in(i, publications, pki, points).ok().map(|values| (i, values)))
+            .collect::<Vec<(u64, Vec<F>)>>()
+            .into_iter()
+            .take(rows_needed as usize)
+            .collect();
+        #[cfg(not(feature = "parallel"))]
+        let verified_rows: Vec<(u64, Vec<F>)> = (1..=n)
+            .filter_map(|i| self.verify_row_in(i, publications, pki, points).ok().map(|values| (i, values)))
+            .take(rows_needed as usize)
+            .collect();
+
+        let n_verified_poly = verified_rows.len() as u64;
+        if n_verified_poly < rows_needed {
+            return Err(Error::NotEnoughShares { needed: rows_needed, verified: n_verified_poly });
+        }
+
+        //By symmetry row i evaluated at the k-th secret point is the k-th column at receiver i's point;
+        //the column evaluated at the secret point itself is the k-th secret
+        let verified_row_keys: Vec<F> = verified_rows.iter().map(|(i, _)| points[(i - 1) as usize]).collect();
+        secret_points.iter().enumerate()
+            .map(|(k, point)| {
+                let column: Vec<F> = verified_rows.iter().map(|(_, values)| values[k]).collect();
+                let unipoly: Poly<F> = Poly::evals_to_coeffs(&verified_row_keys, &column)?;
+                Ok(unipoly.eval(*point))
+            })
+            .collect()
+    }
+}
+
+impl Resolver {
+    ///Dealers that at most t receivers complained about.
+    ///With at most t complaining receivers, at least 2t+1 happy receivers hold consistent rows.
+    pub fn qualify(&self, complaints: &BTreeMap<u64, u64>) -> Vec<u64> {
+        complaints.iter().filter(|(_, complaints)| **complaints <= self.t).map(|(dealer, _)| *dealer).collect()
     }
 }
 
+//Evaluation points of the receivers and the points the packed secrets sit at, which must all differ
+pub(crate) fn points_of<F: Field>(receiver_keys: &[PublicKey], packing: u64) -> Result<(Vec<F>, Vec<F>)> {
+    let points: Vec<F> = evaluation_points_of(receiver_keys)?;
+    let secret_points: Vec<F> = secret_points(packing);
+    if points.iter().any(|point| secret_points.contains(point)) {
+        return Err(Error::InvalidParams("a receiver's evaluation point is one of the secret points".to_string()));
+    }
+    Ok((points, secret_points))
+}
 
-impl<F: Field> Client<'_, F> {
-    pub fn compute_secret(&self, triply_signed_shares: &BTreeMap<u64, BTreeMap<u64, Subshare<F>>>, pki: &Vec<PublicKey>) -> (bool,F) {
-        let mut secret_computable = true; 
-        let n = self.pp.n;
-        let t = self.pp.t;
+///Values of the packed slots, separated by commas
+pub fn display_values<F: Field>(values: &[F]) -> String {
+    values.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(", ")
+}
 
-        let mut verified_shares_of_zero_poly_keys: Vec<u64> = Default::default();
-        let mut verified_shares_of_zero_poly_values: Vec<F> = Default::default();
-        let mut n_verified_poly = 0;
+//Adds `values` slot by slot
+pub(crate) fn add_values<F: Field>(sum: &mut [F], values: &[F]) {
+    for (sum, value) in sum.iter_mut().zip(values) {
+        *sum += value;
+    }
+}
 
-        for i in 1..=n {
+//What the honest roles of one recorded dealing conclude
+struct ReplayedDealing<F: Field> {
+    //Receivers that complained about the dealer
+    complaints: u64,
+    //Secrets the client recovers from the first reconstructor's publication
+    secrets: Option<Vec<F>>,
+}
 
-            //let xvals: Vec<u64> = triply_signed_shares[&i].keys().cloned().collect();
-            //let yvals_tmp: Vec<Subshare<F>> = triply_signed_shares.values().cloned().collect();
-            //triply_signed_shares[&i].values().map(|signed_share| signed_share.value).collect();
-
-            let mut verified_share_keys: Vec<u64> = Default::default();
-            let mut verified_share_values: Vec<F> = Default::default();
-            //let yvals_signed: Vec<Subshare<F>> = triply_signed_shares.values().cloned().collect();
-
-            let mut shares_verified = 0;
-
-            for key in 1..=n {
-                let mut smaller_index;
-                let mut larger_index;
-                if key <= i {
-                    smaller_index = key;
-                    larger_index = i;
-                } else {
-                    smaller_index = i;
-                    larger_index = key;
-                }
-                let share: &Subshare<F> = &triply_signed_shares[&larger_index][&smaller_index];
-
-                let message = share.value;
-                let mut share_bytes = Vec::new();
-                message.serialize_uncompressed(&mut share_bytes).unwrap();
-
-                //let dealer_msg = open(&share.dealer_signature.clone().unwrap(), &pki[0]).unwrap();
-                //let dm_uncompressed: F = F::deserialize_uncompressed(&*dealer_msg).unwrap();
-            
-                //let pi_msg = open(&share.p_i_signature.clone().unwrap(), &pki[smaller_index as usize]).unwrap();
-                //let pi_uncompressed: F = F::deserialize_uncompressed(&*pi_msg).unwrap();
-
-                //let pj_msg = open(&share.p_j_signature.clone().unwrap(), &pki[larger_index as usize]).unwrap();
-                //let pj_uncompressed: F = F::deserialize_uncompressed(&*pj_msg).unwrap();
-
-                if !pki[0].verify(&share_bytes, &share.dealer_signature.unwrap()).is_ok() ||
-                        !pki[smaller_index as usize].verify(&share_bytes, &share.p_i_signature.unwrap()).is_ok() ||
-                        !pki[larger_index as usize].verify(&share_bytes, &share.p_j_signature.unwrap()).is_ok()  {
-                    println!("I'm unhappy!");
-                    break;
-                }
-                verified_share_keys.push(key);
-                verified_share_values.push(message);
-                shares_verified +=1;
-                if shares_verified >= 2*t  + 1 {
-                    break;
-                }
-            }
-            
-            //Skip this party if we don't have enough verified subshares
-            if shares_verified < 2*t  + 1 {
-                continue;
+//Why `sent` is not what an honest receiver i forwards to the reconstructors, given the subshares that passed its checks
+fn forward_problem<F: Field>(i: u64, sent: &BTreeMap<u64, Subshare<F>>, expected: &BTreeMap<u64, Subshare<F>>, dealer_pk: &PublicKey, pk_i: &PublicKey, roots: &VerifiedRoots) -> Option<String> {
+    if !sent.keys().eq(expected.keys()) {
+        return Some(format!("forwarded the subshares of {:?} instead of {:?}", sent.keys().collect::<Vec<_>>(), expected.keys().collect::<Vec<_>>()));
+    }
+    for (j, share) in sent {
+        let received = &expected[j];
+        if share.value != received.value || share.dealer_signature != received.dealer_signature || (*j != i && share.p_i_signature != received.p_i_signature) {
+            return Some(format!("altered the subshare of receiver {}", j));
+        }
+        //Its own subshare carries its signature twice
+        let countersigned = signed_bytes(&share.value, dealer_pk, *j, i).and_then(|share_bytes| {
+            if *j == i {
+                verify_endorsement(&share_bytes, &share.p_i_signature, pk_i, i, roots)?;
             }
+            verify_endorsement(&share_bytes, &share.p_j_signature, pk_i, i, roots)
+        });
+        if let Err(error) = countersigned {
+            return Some(format!("subshare of receiver {}: {}", j, error));
+        }
+    }
+    None
+}
 
-            let unipoly: Poly<F> = Poly::evals_to_coeffs(&verified_share_keys, &verified_share_values, shares_verified);
+//Re-runs every receiver's, reconstructor's and the client's checks on the messages recorded in `session`.
+//Receivers and reconstructors are replayed in the order they speak, each on exactly what was recorded as sent to it.
+//A dealing with more than `max_complaints` complaining receivers is not reconstructed.
+fn replay_dealing<F: Field>(transcript: &Transcript, session: u64, t: u64, n: u64, packing: u64, max_complaints: u64, deviations: &mut Vec<Deviation>) -> Result<ReplayedDealing<F>> {
+    let pki = transcript.keys_of(session)?;
+    if pki.len() as u64 != n + 1 {
+        return Err(Error::Transcript(format!("session {} has {} role keys for a dealer and {} receivers", session, pki.len(), n)));
+    }
+    let (points, _) = points_of::<F>(&pki[1..], packing)?;
+    let degree = t + packing - 1;
+    let roots = VerifiedRoots::default();
+    let mut deviate = |kind: MessageKind, party: u64, reason: String| deviations.push(Deviation::new(session, kind, party, reason));
+    let row_from = |kind: MessageKind, sender: u64, receiver: u64| transcript.message(session, kind, sender, Some(receiver)).and_then(|bytes| decode_row::<F>(bytes).ok());
+
+    let mut complaints = 0;
+    for i in 1..=n {
+        //A row that is missing or does not parse is empty, as for the receiver itself
+        let row = row_from(MessageKind::DealerRow, 0, i).unwrap_or_default();
+        let mut complained = false;
+        for error in row_problems(i, &row, &pki[0], &points, n, degree) {
+            deviate(MessageKind::DealerRow, 0, format!("row of receiver {}: {}", i, error));
+            complained = true;
+        }
 
-            if unipoly.degree > t {
-                println!("I'm unhappy!")
+        //Its row entries go to the later receivers, countersigned
+        for (k, subshare) in row.iter().filter(|(k, _)| (i + 1..=n).contains(*k)) {
+            let problem = match row_from(MessageKind::DoublySignedSubshare, i, *k).as_ref().and_then(|sent| sent.get(k)) {
+                None => Some("sent nothing".to_string()),
+                Some(sent) if sent.value != subshare.value || sent.dealer_signature != subshare.dealer_signature => Some("sent a subshare that is not in its row".to_string()),
+                Some(sent) => signed_bytes(&sent.value, &pki[0], i, *k).and_then(|share_bytes| verify_endorsement(&share_bytes, &sent.p_i_signature, &pki[i as usize], i, &roots)).err().map(|error| error.to_string()),
+            };
+            if let Some(problem) = problem {
+                deviate(MessageKind::DoublySignedSubshare, i, format!("to receiver {}: {}", k, problem));
             }
-            else {
-                n_verified_poly += 1;
-                verified_shares_of_zero_poly_keys.push(i);
-                verified_shares_of_zero_poly_values.push(unipoly.eval(F::ZERO));
+        }
+
+        //Subshares that pass its checks are the ones it has to forward, its own included
+        let mut expected: BTreeMap<u64, Subshare<F>> = BTreeMap::new();
+        for j in 1..i {
+            let share = match row_from(MessageKind::DoublySignedSubshare, j, i).and_then(|mut sent| sent.remove(&i)) {
+                Some(share) => share,
+                None => continue,
+            };
+            match check_subshare(&row, j, i, &share, &pki[0], &pki[j as usize], &roots) {
+                Ok(_) => { expected.insert(j, share); },
+                Err(Error::InconsistentValue { .. }) => complained = true,
+                Err(_) => {},
             }
-            if n_verified_poly >= t  + 1 {
-                break;
+        }
+        if let Some(own) = row.get(&i) {
+            if signed_bytes(&own.value, &pki[0], i, i).and_then(|share_bytes| verify_endorsement(&share_bytes, &own.dealer_signature, &pki[0], 0, &roots)).is_ok() {
+                expected.insert(i, own.clone());
             }
         }
+        if complained {
+            complaints += 1;
+        }
 
+        for j in 1..=t+1 {
+            let problem = match row_from(MessageKind::TriplySignedSubshares, i, n + j) {
+                None => Some("sent nothing".to_string()),
+                Some(sent) => forward_problem(i, &sent, &expected, &pki[0], &pki[i as usize], &roots),
+            };
+            if let Some(problem) = problem {
+                deviate(MessageKind::TriplySignedSubshares, i, format!("to reconstructor {}: {}", j, problem));
+            }
+        }
+    }
+
+    if complaints > max_complaints {
+        return Ok(ReplayedDealing { complaints: complaints, secrets: None });
+    }
 
-        if n_verified_poly < t  + 1 {
-            println!("I'm unhappy!")
+    //Reconstructors (ids as in PubParams::reconstructor_id) publish exactly what was forwarded to them
+    let mut publications = Vec::new();
+    for j in 1..=t+1 {
+        let received: BTreeMap<u64, BTreeMap<u64, Subshare<F>>> = (1..=n)
+            .map(|from| (from, row_from(MessageKind::TriplySignedSubshares, from, n + j).unwrap_or_default()))
+            .collect();
+        let published = transcript.message(session, MessageKind::Publication, n + j, None);
+        if published != Some(&encode_publication(&received)?[..]) {
+            deviate(MessageKind::Publication, n + j, "published something else than it received".to_string());
         }
+        publications.push(published.and_then(decode_publication::<F>).unwrap_or_default());
+    }
+
+    //The client reads every publication, as in reconstruct_phase
+    let client: Client<F> = Client::new(t, n).with_packing(packing);
+    let secrets = client.compute_secret(&publications, &pki, &mut CostReport::default()).ok();
+    Ok(ReplayedDealing { complaints: complaints, secrets: secrets })
+}
+
+///Publications recorded in `session` that parse, in the order of the reconstructors
+pub fn publications_of<F: Field>(transcript: &Transcript, session: u64, t: u64, n: u64) -> Vec<BTreeMap<u64, BTreeMap<u64, Subshare<F>>>> {
+    (1..=t+1)
+        .filter_map(|j| transcript.message(session, MessageKind::Publication, n + j, None).and_then(decode_publication::<F>))
+        .collect()
+}
 
-        let unipoly: Poly<F> = Poly::evals_to_coeffs(&verified_shares_of_zero_poly_keys, &verified_shares_of_zero_poly_values, n_verified_poly);
+//Claimed output of a VSS or beacon transcript
+pub(crate) fn claimed_output<F: Field>(transcript: &Transcript) -> Result<Vec<F>> {
+    Vec::<F>::deserialize_compressed(&transcript.output[..]).map_err(|_| Error::Transcript("the claimed output is not a list of field elements".to_string()))
+}
 
-        let secret: F = unipoly.eval(F::ZERO);
-        secret_computable = n_verified_poly > t;
-        (secret_computable, secret)
+impl<F: Field> VSS<F> {
+    ///Replays a transcript of execute_recorded offline: flags every message an honest role would not have sent
+    ///and recomputes the secret the client recovers
+    pub fn verify_transcript(transcript: &Transcript) -> Result<Verification<Vec<F>>> {
+        let (t, n, packing) = match transcript.protocol {
+            Protocol::Vss { t, n, packing } => (t, n, packing),
+            _ => return Err(Error::Transcript("not a VSS transcript".to_string())),
+        };
+        let mut deviations = Vec::new();
+        let dealing: ReplayedDealing<F> = replay_dealing(transcript, 0, t, n, packing, n, &mut deviations)?;
+        let output = dealing.secrets.unwrap_or_else(|| vec![F::ZERO; packing as usize]);
+        Ok(Verification { output: output, claimed: claimed_output(transcript)?, deviations: deviations })
     }
 }
 
+impl<F: Field> RandExtractorVSSBased<F> {
+    ///Replays every dealing of a beacon transcript, requalifies the dealers from the replayed complaints
+    ///and adds up the qualified secrets slot by slot
+    pub fn verify_transcript(transcript: &Transcript) -> Result<Verification<Vec<F>>> {
+        let (t, n, packing) = match transcript.protocol {
+            Protocol::Beacon { t, n, packing } => (t, n, packing),
+            _ => return Err(Error::Transcript("not a beacon transcript".to_string())),
+        };
+        let mut deviations = Vec::new();
+        let dealings: BTreeMap<u64, ReplayedDealing<F>> = (1..=n)
+            .map(|dealer| Ok((dealer, replay_dealing(transcript, dealer, t, n, packing, t, &mut deviations)?)))
+            .collect::<Result<BTreeMap<u64, ReplayedDealing<F>>>>()?;
+        let complaints: BTreeMap<u64, u64> = dealings.iter().map(|(dealer, dealing)| (*dealer, dealing.complaints)).collect();
+        let mut output = vec![F::ZERO; packing as usize];
+        for dealer in (Resolver { t: t }).qualify(&complaints) {
+            if let Some(secrets) = &dealings[&dealer].secrets {
+                add_values(&mut output, secrets);
+            }
+        }
+        Ok(Verification { output: output, claimed: claimed_output(transcript)?, deviations: deviations })
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fq;
+    use rand::rngs::OsRng;
+
+    use crate::role_assignment::generate_keypair;
+
+    use super::*;
+
+    #[test]
+    fn a_merkle_endorsement_holds_only_at_its_position() {
+        let dealer = generate_keypair(&mut OsRng);
+        let other = generate_keypair(&mut OsRng);
+        let value = Fq::from(7u64);
+        //Two entries with the same value, as in the symmetric matrix
+        let leaves = vec![signed_bytes(&value, &dealer.public, 1, 2).unwrap(), signed_bytes(&value, &dealer.public, 1, 3).unwrap()];
+        let endorsement = Some(endorse(&leaves, &dealer, Authentication::Merkle).unwrap().remove(0));
+        let roots = VerifiedRoots::default();
+
+        let at = |i: u64, j: u64, dealer_pk: &PublicKey| signed_bytes(&value, dealer_pk, i, j).unwrap();
+        assert!(verify_endorsement(&at(1, 2, &dealer.public), &endorsement, &dealer.public, 0, &roots).is_ok());
+        assert!(verify_endorsement(&at(2, 1, &dealer.public), &endorsement, &dealer.public, 0, &roots).is_ok());
+        assert!(verify_endorsement(&at(1, 3, &dealer.public), &endorsement, &dealer.public, 0, &roots).is_err());
+        assert!(verify_endorsement(&at(1, 2, &other.public), &endorsement, &dealer.public, 0, &roots).is_err());
+    }
 
+    #[test]
+    fn the_same_seed_nominates_the_same_committees() {
+        let pp = PubParams::with_size_and_seed(1, 4, 9, 5).unwrap();
+        let again = PubParams::with_size_and_seed(1, 4, 9, 5).unwrap();
+        assert_eq!(pp.roles.signing_keys(), again.roles.signing_keys());
+        assert_eq!(pp.next_committee().unwrap().roles.signing_keys(), again.next_committee().unwrap().roles.signing_keys());
+        assert_ne!(pp.next_committee().unwrap().roles.signing_keys(), pp.roles.signing_keys());
+        assert_ne!(PubParams::with_size_and_seed(1, 4, 9, 6).unwrap().roles.signing_keys(), pp.roles.signing_keys());
+    }
 
+    #[test]
+    fn a_committee_member_deals_with_its_receiver_keys_to_its_own_committee() {
+        let pp = PubParams::new(1).unwrap();
+        let keys = pp.roles.signing_keys();
+        for dealer in 1..=pp.n {
+            let dealing = pp.dealing_to(dealer, &pp).unwrap();
+            let dealing_keys = dealing.roles.signing_keys();
+            assert_eq!(dealing_keys[0], keys[dealer as usize]);
+            assert_eq!(dealing_keys[1..], keys[1..]);
+            assert_eq!(dealing.claim_role(0).unwrap().keypair.public, keys[dealer as usize]);
+        }
+    }
 
-//let message = Subshare { value: F::ONE, dealer_signature: None, pi_signature: None, pj_signature: None };
-//let mut message_bytes = Vec::new();
-//F::ONE.serialize_uncompressed(&mut message_bytes).unwrap();
-//let sm = sign(&message_bytes, &sk);
-//let verifiedmsg = open(&sm, &pk).unwrap();
-//let m_uncompressed: F = F::deserialize_uncompressed(&*verifiedmsg).unwrap();
\ No newline at end of file
+    #[test]
+    fn forgeries_from_leaked_keys_do_not_survive_and_t_rows_keep_the_secret() {
+        for t in 1..=2 {
+            let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(5u64)], pp: PubParams::new(t).unwrap(), execution_leaks: true, seed: 6 };
+            let report = vss.execute().unwrap();
+            let leakage = report.leakage.unwrap();
+            assert!(leakage.forgeries_attempted > 0);
+            assert_eq!(leakage.forgeries_accepted, 0);
+            assert!(leakage.secrecy_holds);
+            assert!(report.success);
+        }
+    }
+
+    #[test]
+    fn a_packed_dealing_opens_every_slot_and_t_rows_keep_all_of_them() {
+        let secrets: Vec<Fq> = [4u64, 9, 16].iter().map(|secret| Fq::from(*secret)).collect();
+        let vss: VSS<Fq> = VSS { secrets: secrets.clone(), pp: PubParams::packed(1, 3).unwrap(), execution_leaks: true, seed: 6 };
+        assert_eq!((vss.pp.n, vss.pp.degree()), (6, 3));
+        let report = vss.execute().unwrap();
+        assert!(report.reconstructed && report.success);
+        assert_eq!(report.secrets, secrets);
+        assert!(report.leakage.unwrap().secrecy_holds);
+
+        //Every further slot raises the degree, so the receivers of a single secret are too few
+        assert!(PubParams::with_size(1, 4, 9).unwrap().with_packing(3).validate().is_err());
+        assert!(PubParams::new(1).unwrap().with_packing(0).validate().is_err());
+    }
+
+    #[test]
+    fn bad_inputs_to_the_roles_come_back_as_errors() {
+        let pp = PubParams::new(1).unwrap();
+        assert!(matches!(Dealer::<Fq>::new(vec![Fq::from(1u64); 2], &pp, 0), Err(Error::InvalidParams(_))));
+        assert!(matches!(Receiver::<Fq>::new(pp.n + 1, &pp), Err(Error::InvalidParams(_))));
+        let pki = pp.roles.signing_keys();
+        let client: Client<Fq> = Client::new(pp.t, pp.n);
+        assert!(matches!(client.compute_secret(&[], &pki, &mut CostReport::default()), Err(Error::NotEnoughShares { needed: 2, verified: 0 })));
+        assert!(matches!(client.compute_secret(&[], &pki[1..], &mut CostReport::default()), Err(Error::NotEnoughShares { .. })));
+    }
+
+    #[test]
+    fn t_plus_one_leaked_rows_give_the_secret_away() {
+        let t = 2;
+        let vss: VSS<Fq> = VSS { secrets: vec![Fq::from(5u64)], pp: PubParams::new(t).unwrap(), execution_leaks: true, seed: 6 };
+        let mut adversary: LeakageAdversary<Fq> = LeakageAdversary::new(t, vss.pp.n, 6);
+        adversary.leaked_receivers = (1..=t+1).collect();
+        let sharing = vss.share_phase(&mut CostReport::default(), Some(&mut adversary), &mut Honest, None).unwrap();
+        vss.reconstruct_phase(&sharing, &mut CostReport::default(), Some(&mut adversary), &mut Honest, None).unwrap();
+        let (points, secret_points) = points_of::<Fq>(&vss.pp.roles.signing_keys()[1..], 1).unwrap();
+        let leakage = adversary.report(&points, &secret_points, vss.pp.degree());
+        assert_eq!(leakage.leaked_rows as u64, t + 1);
+        assert!(!leakage.secrecy_holds);
+    }
+}